@@ -0,0 +1,104 @@
+//! Compares the spatial grid against testing every pair on a crowded screen.
+//!
+//! Run with `cargo bench --bench collision`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use cgmath::{MetricSpace, Vector2};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use spaceshooter::game::spatial::{SpatialGrid, COLLISION_CELL_SIZE};
+
+/// About one screen at the game's zoom.
+const AREA: Vector2<f32> = Vector2::new(1800.0, 1000.0);
+
+struct Circle {
+    pos: Vector2<f32>,
+    radius: f32,
+}
+
+fn scatter(rng: &mut ChaCha8Rng, count: usize, radius: f32) -> Vec<Circle> {
+    (0..count)
+        .map(|_| Circle {
+            pos: Vector2::new(rng.gen_range(0.0..AREA.x), rng.gen_range(0.0..AREA.y)),
+            radius,
+        })
+        .collect()
+}
+
+/// Counts the bullet hits and enemy collisions by testing every pair.
+fn brute_force(enemies: &[Circle], bullets: &[Circle]) -> usize {
+    let mut hits = 0;
+    for bullet in bullets {
+        for enemy in enemies {
+            if bullet.pos.distance(enemy.pos) < bullet.radius + enemy.radius {
+                hits += 1;
+            }
+        }
+    }
+    for (index, enemy) in enemies.iter().enumerate() {
+        for other in &enemies[index + 1..] {
+            if enemy.pos.distance(other.pos) < enemy.radius + other.radius {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+/// Counts the same hits as `brute_force` through a grid of the enemies.
+fn grid(enemies: &[Circle], bullets: &[Circle]) -> usize {
+    let grid = SpatialGrid::new(
+        COLLISION_CELL_SIZE,
+        enemies
+            .iter()
+            .enumerate()
+            .map(|(index, enemy)| (index, enemy.pos, enemy.radius)),
+    );
+    let mut hits = 0;
+    for bullet in bullets {
+        hits += grid.overlapping(bullet.pos, bullet.radius).count();
+    }
+    for (index, enemy) in enemies.iter().enumerate() {
+        hits += grid
+            .overlapping(enemy.pos, enemy.radius)
+            .filter(|other| *other > index)
+            .count();
+    }
+    hits
+}
+
+/// The fastest of a few runs, to keep other work on the machine out of the result.
+fn measure(mut run: impl FnMut() -> usize) -> (Duration, usize) {
+    let mut best = Duration::MAX;
+    let mut hits = 0;
+    for _ in 0..10 {
+        let start = Instant::now();
+        hits = black_box(run());
+        best = best.min(start.elapsed());
+    }
+    (best, hits)
+}
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    for (enemy_count, bullet_count) in [(20, 200), (100, 1000), (300, 3000), (1000, 10000)] {
+        let enemies = scatter(&mut rng, enemy_count, 16.0);
+        let bullets = scatter(&mut rng, bullet_count, 10.0);
+
+        let (brute_force_time, brute_force_hits) = measure(|| brute_force(&enemies, &bullets));
+        let (grid_time, grid_hits) = measure(|| grid(&enemies, &bullets));
+        assert_eq!(
+            brute_force_hits, grid_hits,
+            "the grid missed or repeated hits"
+        );
+
+        println!(
+            "{enemy_count:>5} enemies {bullet_count:>6} bullets: brute force {brute_force_time:>10.2?}, grid {grid_time:>10.2?} ({:.1}x)",
+            brute_force_time.as_secs_f64() / grid_time.as_secs_f64()
+        );
+    }
+}
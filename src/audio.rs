@@ -0,0 +1,473 @@
+use cgmath::{InnerSpace, Vector2};
+
+use crate::game::{Engine, GameEvent, VIEW_HEIGHT};
+
+mod synth;
+
+pub use synth::synthesize;
+
+pub const SAMPLE_RATE: u32 = 44100;
+
+/// How far from the camera a sound is at half volume.
+const HEARING_DISTANCE: f32 = VIEW_HEIGHT;
+/// How many engines can be heard at once. The loudest get the channels.
+const ENGINE_CHANNELS: usize = 6;
+const MUSIC_CHANNEL: usize = ENGINE_CHANNELS;
+const ENGINE_VOLUME: f32 = 0.15;
+const MUSIC_VOLUME: f32 = 0.35;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Shot,
+    EnemyShot,
+    Hit,
+    PartHit,
+    Explosion,
+    PartDestroyed,
+    PickUp,
+    Engine,
+    Music,
+}
+
+impl Sound {
+    pub const ALL: [Sound; 9] = [
+        Sound::Shot,
+        Sound::EnemyShot,
+        Sound::Hit,
+        Sound::PartHit,
+        Sound::Explosion,
+        Sound::PartDestroyed,
+        Sound::PickUp,
+        Sound::Engine,
+        Sound::Music,
+    ];
+}
+
+/// Where sound goes, so a real audio device can be swapped for silence.
+pub trait AudioBackend {
+    /// Plays `sound` once. `pan` goes from -1 (left) to 1 (right).
+    fn play(&mut self, sound: Sound, volume: f32, pan: f32);
+
+    /// Keeps `sound` looping on `channel`, changing its volume and pan if it already is.
+    fn set_loop(&mut self, channel: usize, sound: Sound, volume: f32, pan: f32);
+}
+
+/// Plays nothing, for running without a sound device.
+#[derive(Default)]
+pub struct NullAudio;
+
+impl AudioBackend for NullAudio {
+    fn play(&mut self, _sound: Sound, _volume: f32, _pan: f32) {}
+
+    fn set_loop(&mut self, _channel: usize, _sound: Sound, _volume: f32, _pan: f32) {}
+}
+
+#[cfg(feature = "audio")]
+pub use device::RodioAudio;
+
+#[cfg(feature = "audio")]
+mod device {
+    use rodio::{OutputStream, OutputStreamHandle, Source};
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use super::{synthesize, AudioBackend, Sound, SAMPLE_RATE};
+
+    /// Sound through the default output device.
+    pub struct RodioAudio {
+        // Dropping the stream stops all sound.
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        sounds: HashMap<Sound, Arc<[f32]>>,
+        loops: HashMap<usize, LoopControl>,
+        /// Whether a failure to play has been reported, since it tends to repeat every frame.
+        warned: bool,
+    }
+
+    impl RodioAudio {
+        pub fn new() -> Result<Self, rodio::StreamError> {
+            let (stream, handle) = OutputStream::try_default()?;
+            Ok(Self {
+                _stream: stream,
+                handle,
+                sounds: Sound::ALL
+                    .iter()
+                    .map(|sound| (*sound, synthesize(*sound).into()))
+                    .collect(),
+                loops: HashMap::new(),
+                warned: false,
+            })
+        }
+
+        fn warn(&mut self, error: rodio::PlayError) {
+            if !self.warned {
+                eprintln!("failed to play a sound: {error}, not reporting any more failures");
+                self.warned = true;
+            }
+        }
+    }
+
+    impl AudioBackend for RodioAudio {
+        fn play(&mut self, sound: Sound, volume: f32, pan: f32) {
+            let source = Voice {
+                samples: self.sounds[&sound].clone(),
+                position: 0,
+                looping: false,
+                gains: Arc::new(Gains::new(volume, pan)),
+                current: [0.0; 2],
+                smoothing: 1.0,
+            };
+            if let Err(error) = self.handle.play_raw(source) {
+                self.warn(error);
+            }
+        }
+
+        fn set_loop(&mut self, channel: usize, sound: Sound, volume: f32, pan: f32) {
+            if let Some(control) = self.loops.get(&channel) {
+                if control.sound == sound {
+                    control.gains.set(volume, pan);
+                    return;
+                }
+                // The old voice fades itself out and finishes.
+                control.gains.stop();
+            }
+            let gains = Arc::new(Gains::new(volume, pan));
+            let source = Voice {
+                samples: self.sounds[&sound].clone(),
+                position: 0,
+                looping: true,
+                gains: gains.clone(),
+                current: [0.0; 2],
+                smoothing: 0.001,
+            };
+            if let Err(error) = self.handle.play_raw(source) {
+                self.warn(error);
+                return;
+            }
+            self.loops.insert(channel, LoopControl { sound, gains });
+        }
+    }
+
+    struct LoopControl {
+        sound: Sound,
+        gains: Arc<Gains>,
+    }
+
+    /// Left and right volume, shared with the audio thread.
+    struct Gains {
+        left: AtomicU32,
+        right: AtomicU32,
+        stopped: AtomicU32,
+    }
+
+    impl Gains {
+        fn new(volume: f32, pan: f32) -> Self {
+            let gains = Self {
+                left: AtomicU32::new(0),
+                right: AtomicU32::new(0),
+                stopped: AtomicU32::new(0),
+            };
+            gains.set(volume, pan);
+            gains
+        }
+
+        /// Constant power panning, so sounds don't get quieter in the middle.
+        fn set(&self, volume: f32, pan: f32) {
+            let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+            self.left
+                .store((volume * angle.cos()).to_bits(), Ordering::Relaxed);
+            self.right
+                .store((volume * angle.sin()).to_bits(), Ordering::Relaxed);
+        }
+
+        fn stop(&self) {
+            self.set(0.0, 0.0);
+            self.stopped.store(1, Ordering::Relaxed);
+        }
+
+        fn get(&self) -> [f32; 2] {
+            [
+                f32::from_bits(self.left.load(Ordering::Relaxed)),
+                f32::from_bits(self.right.load(Ordering::Relaxed)),
+            ]
+        }
+
+        fn is_stopped(&self) -> bool {
+            self.stopped.load(Ordering::Relaxed) != 0
+        }
+    }
+
+    /// A mono sound played in stereo, easing towards new gains instead of jumping to them.
+    struct Voice {
+        samples: Arc<[f32]>,
+        /// Index of the next sample, times two for the two channels.
+        position: usize,
+        looping: bool,
+        gains: Arc<Gains>,
+        current: [f32; 2],
+        /// How far the gains move towards their target each frame, from 0 to 1.
+        smoothing: f32,
+    }
+
+    impl Iterator for Voice {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            let mut index = self.position / 2;
+            if index >= self.samples.len() {
+                if !self.looping {
+                    return None;
+                }
+                self.position = 0;
+                index = 0;
+            }
+            let channel = self.position % 2;
+            if channel == 0 {
+                let target = self.gains.get();
+                if self.gains.is_stopped() && self.current.iter().all(|gain| *gain < 1e-4) {
+                    return None;
+                }
+                for (current, target) in self.current.iter_mut().zip(target) {
+                    *current += (target - *current) * self.smoothing;
+                }
+            }
+            self.position += 1;
+            Some(self.samples[index] * self.current[channel])
+        }
+    }
+
+    impl Source for Voice {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            2
+        }
+
+        fn sample_rate(&self) -> u32 {
+            SAMPLE_RATE
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+}
+
+/// Turns what happens in the game into sound: one-shots for events, loops for engines and music.
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    sounds_played: u64,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Self {
+        Self {
+            backend,
+            sounds_played: 0,
+        }
+    }
+
+    pub fn sounds_played(&self) -> u64 {
+        self.sounds_played
+    }
+
+    /// Plays a sound for each of a tick's events, placed relative to `camera`. Several of the
+    /// same sound in one tick only play once, as loud as the loudest of them.
+    pub fn play_events(&mut self, events: &[GameEvent], camera: Vector2<f32>) {
+        let mut loudest: Vec<(Sound, f32, f32)> = vec![];
+        for event in events {
+            let Some((sound, pos)) = event_sound(event) else {
+                continue;
+            };
+            let (volume, pan) = placement(pos, camera);
+            match loudest.iter_mut().find(|(other, ..)| *other == sound) {
+                Some(entry) if entry.1 < volume => *entry = (sound, volume, pan),
+                Some(_) => {}
+                None => loudest.push((sound, volume, pan)),
+            }
+        }
+        for (sound, volume, pan) in loudest {
+            self.backend.play(sound, volume, pan);
+            self.sounds_played += 1;
+        }
+    }
+
+    /// Keeps the engine loops following the loudest `engines` and the music going. Engines are
+    /// silent while `playing` is false.
+    pub fn update(&mut self, engines: &[Engine], camera: Vector2<f32>, playing: bool) {
+        let mut voices: Vec<(f32, f32)> = if playing {
+            engines
+                .iter()
+                .map(|engine| {
+                    let (volume, pan) = placement(engine.pos, camera);
+                    (volume * engine.throttle.min(2.0) * ENGINE_VOLUME, pan)
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        voices.sort_by(|a, b| b.0.total_cmp(&a.0));
+        voices.resize(ENGINE_CHANNELS.max(voices.len()), (0.0, 0.0));
+        for (channel, (volume, pan)) in voices.into_iter().take(ENGINE_CHANNELS).enumerate() {
+            self.backend.set_loop(channel, Sound::Engine, volume, pan);
+        }
+        self.backend
+            .set_loop(MUSIC_CHANNEL, Sound::Music, MUSIC_VOLUME, 0.0);
+    }
+}
+
+fn event_sound(event: &GameEvent) -> Option<(Sound, Vector2<f32>)> {
+    Some(match *event {
+        GameEvent::BulletFired { pos, friendly, .. } => (
+            if friendly {
+                Sound::Shot
+            } else {
+                Sound::EnemyShot
+            },
+            pos,
+        ),
+        GameEvent::EnemyHit { pos } => (Sound::Hit, pos),
+        GameEvent::EnemyKilled { pos, .. } => (Sound::Explosion, pos),
+        GameEvent::PartDamaged { pos, .. } => (Sound::PartHit, pos),
+        GameEvent::PartDestroyed { pos, .. } => (Sound::PartDestroyed, pos),
+        GameEvent::PowerUpCollected { pos, .. } => (Sound::PickUp, pos),
+        GameEvent::WaveSpawned { .. } => return None,
+    })
+}
+
+/// Volume and pan for a sound at `pos`, fading with distance from `camera` and panned by
+/// which side of it the sound is on.
+fn placement(pos: Vector2<f32>, camera: Vector2<f32>) -> (f32, f32) {
+    let offset = pos - camera;
+    let volume = 1.0 / (1.0 + (offset.magnitude() / HEARING_DISTANCE).powi(2));
+    let pan = (offset.x / HEARING_DISTANCE).clamp(-1.0, 1.0);
+    (volume, pan)
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Zero;
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    /// What was sent to the backend: one-shots, and the latest loop on each channel.
+    #[derive(Default)]
+    struct Log {
+        played: Vec<(Sound, f32, f32)>,
+        loops: Vec<Option<(Sound, f32, f32)>>,
+    }
+
+    struct RecordingAudio(Rc<RefCell<Log>>);
+
+    impl AudioBackend for RecordingAudio {
+        fn play(&mut self, sound: Sound, volume: f32, pan: f32) {
+            self.0.borrow_mut().played.push((sound, volume, pan));
+        }
+
+        fn set_loop(&mut self, channel: usize, sound: Sound, volume: f32, pan: f32) {
+            let loops = &mut self.0.borrow_mut().loops;
+            if loops.len() <= channel {
+                loops.resize(channel + 1, None);
+            }
+            loops[channel] = Some((sound, volume, pan));
+        }
+    }
+
+    fn recording() -> (Audio, Rc<RefCell<Log>>) {
+        let log = Rc::new(RefCell::new(Log::default()));
+        (Audio::new(Box::new(RecordingAudio(log.clone()))), log)
+    }
+
+    fn shot(x: f32) -> GameEvent {
+        GameEvent::BulletFired {
+            pos: Vector2::new(x, 0.0),
+            vel: Vector2::new(0.0, 500.0),
+            friendly: true,
+        }
+    }
+
+    #[test]
+    fn one_of_each_sound_per_tick() {
+        let (mut audio, log) = recording();
+        let events = [
+            shot(2000.0),
+            GameEvent::EnemyHit {
+                pos: Vector2::new(0.0, 0.0),
+            },
+            shot(-300.0),
+            shot(900.0),
+        ];
+        audio.play_events(&events, Vector2::new(0.0, 0.0));
+        let played = &log.borrow().played;
+        assert_eq!(played.len(), 2);
+        assert_eq!(audio.sounds_played(), 2);
+        // The nearest shot is the loudest, so it is the one played.
+        let (sound, volume, pan) = played[0];
+        assert_eq!(sound, Sound::Shot);
+        assert_eq!(
+            (volume, pan),
+            placement(Vector2::new(-300.0, 0.0), Vector2::zero())
+        );
+        assert_eq!(played[1], (Sound::Hit, 1.0, 0.0));
+    }
+
+    #[test]
+    fn panned_by_side_of_the_camera() {
+        let (mut audio, log) = recording();
+        let camera = Vector2::new(500.0, 500.0);
+        for x in [500.0 - HEARING_DISTANCE * 2.0, 250.0, 500.0, 1000.0] {
+            audio.play_events(&[shot(x)], camera);
+        }
+        let pans: Vec<_> = log.borrow().played.iter().map(|played| played.2).collect();
+        assert_eq!(
+            pans,
+            [
+                -1.0,
+                -250.0 / HEARING_DISTANCE,
+                0.0,
+                500.0 / HEARING_DISTANCE
+            ]
+        );
+    }
+
+    #[test]
+    fn loudest_engines_get_the_channels() {
+        let (mut audio, log) = recording();
+        let throttles = [0.1, 0.9, 0.3, 1.5, 0.0, 0.7, 0.2, 1.0, 0.5];
+        let engines: Vec<_> = throttles
+            .iter()
+            .map(|throttle| Engine {
+                pos: Vector2::zero(),
+                throttle: *throttle,
+            })
+            .collect();
+        audio.update(&engines, Vector2::zero(), true);
+        let volumes: Vec<_> = log.borrow().loops[..ENGINE_CHANNELS]
+            .iter()
+            .map(|engine| {
+                let (sound, volume, _) = engine.unwrap();
+                assert_eq!(sound, Sound::Engine);
+                volume / ENGINE_VOLUME
+            })
+            .collect();
+        assert_eq!(volumes, [1.5, 1.0, 0.9, 0.7, 0.5, 0.3]);
+        assert_eq!(
+            log.borrow().loops[MUSIC_CHANNEL],
+            Some((Sound::Music, MUSIC_VOLUME, 0.0))
+        );
+
+        audio.update(&engines, Vector2::zero(), false);
+        assert!(log.borrow().loops[..ENGINE_CHANNELS]
+            .iter()
+            .all(|engine| engine.unwrap().1 == 0.0));
+    }
+}
@@ -0,0 +1,165 @@
+//! Every sound is made here from oscillators and noise, so the game needs no audio files.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::f32::consts::TAU;
+
+use super::{Sound, SAMPLE_RATE};
+
+/// One channel of samples for `sound`. Looping sounds repeat seamlessly.
+pub fn synthesize(sound: Sound) -> Vec<f32> {
+    match sound {
+        Sound::Shot => sweep(0.08, 1400.0, 300.0, square, 0.35),
+        Sound::EnemyShot => sweep(0.12, 500.0, 120.0, saw, 0.3),
+        Sound::Hit => mix(
+            noise_burst(0.05, 0.6, 0.5),
+            sweep(0.05, 900.0, 600.0, sine, 0.3),
+        ),
+        Sound::PartHit => mix(
+            noise_burst(0.15, 0.15, 0.7),
+            sweep(0.15, 180.0, 60.0, sine, 0.6),
+        ),
+        Sound::Explosion => mix(
+            noise_burst(0.7, 0.08, 0.9),
+            sweep(0.4, 120.0, 30.0, sine, 0.6),
+        ),
+        Sound::PartDestroyed => mix(
+            noise_burst(1.4, 0.04, 1.0),
+            sweep(0.9, 90.0, 20.0, saw, 0.5),
+        ),
+        Sound::PickUp => arpeggio(&[523.25, 659.25, 783.99, 1046.5], 0.07, 0.4),
+        Sound::Engine => engine(),
+        Sound::Music => music(),
+    }
+}
+
+fn samples(duration: f32) -> usize {
+    (duration * SAMPLE_RATE as f32) as usize
+}
+
+/// Fades in over the first couple of milliseconds, since starting at full volume clicks.
+fn attack(index: usize) -> f32 {
+    (index as f32 / samples(0.002) as f32).min(1.0)
+}
+
+fn sine(phase: f32) -> f32 {
+    (phase * TAU).sin()
+}
+
+fn square(phase: f32) -> f32 {
+    if phase.fract() < 0.5 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn saw(phase: f32) -> f32 {
+    phase.fract() * 2.0 - 1.0
+}
+
+/// A tone sliding from `from` to `to` hertz over `duration` seconds while fading out.
+fn sweep(duration: f32, from: f32, to: f32, wave: fn(f32) -> f32, volume: f32) -> Vec<f32> {
+    let count = samples(duration);
+    let mut phase = 0.0;
+    (0..count)
+        .map(|index| {
+            let t = index as f32 / count as f32;
+            phase += (from + (to - from) * t) / SAMPLE_RATE as f32;
+            wave(phase) * attack(index) * (1.0 - t).powi(2) * volume
+        })
+        .collect()
+}
+
+/// Noise fading out over `duration` seconds. A lower `brightness` makes it duller, from 0 to 1.
+fn noise_burst(duration: f32, brightness: f32, volume: f32) -> Vec<f32> {
+    let count = samples(duration);
+    let mut rng = ChaCha8Rng::seed_from_u64(count as u64);
+    let mut filtered = 0.0;
+    (0..count)
+        .map(|index| {
+            let t = index as f32 / count as f32;
+            filtered += (rng.gen_range(-1.0..1.0) - filtered) * brightness;
+            filtered * attack(index) * (1.0 - t).powi(3) * volume
+        })
+        .collect()
+}
+
+fn mix(mut a: Vec<f32>, b: Vec<f32>) -> Vec<f32> {
+    if a.len() < b.len() {
+        a.resize(b.len(), 0.0);
+    }
+    for (a, b) in a.iter_mut().zip(b) {
+        *a += b;
+    }
+    a
+}
+
+/// `notes` in hertz played one after the other, each `step` seconds long.
+fn arpeggio(notes: &[f32], step: f32, volume: f32) -> Vec<f32> {
+    let note_samples = samples(step);
+    let mut output = vec![];
+    for (index, frequency) in notes.iter().enumerate() {
+        // Later notes ring on longer so the last one doesn't cut off.
+        let length = note_samples * (1 + index * 2);
+        let mut note: Vec<f32> = (0..length)
+            .map(|sample| {
+                let t = sample as f32 / length as f32;
+                sine(frequency * sample as f32 / SAMPLE_RATE as f32) * (1.0 - t).powi(2) * volume
+            })
+            .collect();
+        let start = note_samples * index;
+        output.resize(output.len().max(start + note.len()), 0.0);
+        for (output, note) in output[start..].iter_mut().zip(note.drain(..)) {
+            *output += note;
+        }
+    }
+    output
+}
+
+/// A second of low hum. Every frequency is a whole number of hertz, so it loops without a click.
+fn engine() -> Vec<f32> {
+    let rate = SAMPLE_RATE as f32;
+    (0..SAMPLE_RATE as usize)
+        .map(|index| {
+            let time = index as f32 / rate;
+            let wobble = 1.0 + 0.2 * sine(6.0 * time);
+            (saw(50.0 * time) * 0.4 + saw(75.0 * time) * 0.25 + sine(101.0 * time) * 0.2) * wobble
+        })
+        .collect()
+}
+
+/// A slow eight bar loop of bass under arpeggiated chords.
+fn music() -> Vec<f32> {
+    const BEAT: f32 = 0.375;
+    const BAR: usize = 8;
+    // A minor, F major, C major and G major, as (bass, chord notes) in hertz.
+    const CHORDS: [(f32, [f32; 3]); 4] = [
+        (55.0, [220.0, 261.63, 329.63]),
+        (43.65, [174.61, 220.0, 261.63]),
+        (65.41, [261.63, 329.63, 392.0]),
+        (49.0, [196.0, 246.94, 293.66]),
+    ];
+    let beat_samples = samples(BEAT);
+    let mut output = vec![0.0; beat_samples * BAR * CHORDS.len() * 2];
+    let length = output.len();
+    for beat in 0..length / beat_samples {
+        let (bass, chord) = CHORDS[beat / BAR % CHORDS.len()];
+        let start = beat * beat_samples;
+        let note = chord[beat % chord.len()] * if beat % BAR >= 6 { 2.0 } else { 1.0 };
+        for sample in 0..beat_samples {
+            let time = sample as f32 / SAMPLE_RATE as f32;
+            let t = sample as f32 / beat_samples as f32;
+            let pluck = sine(note * time) * (1.0 - t).powi(3) * 0.25;
+            // The bass holds for the whole bar, so it only fades in and out at its ends.
+            let bar_t = ((beat % BAR) as f32 + t) / BAR as f32;
+            let bass_envelope = (bar_t * 20.0).min(1.0) * ((1.0 - bar_t) * 20.0).min(1.0);
+            let bass =
+                saw(bass * (start % (beat_samples * BAR) + sample) as f32 / SAMPLE_RATE as f32)
+                    * bass_envelope
+                    * 0.2;
+            output[start + sample] += pluck + bass;
+        }
+    }
+    output
+}
@@ -1,45 +1,113 @@
-use crate::renderer::{self, texture::TextureId, FrameRendering, Renderer, Rendering2D};
+use crate::{
+    input::PlayerInput,
+    renderer::{
+        particals::ParticalSpawn,
+        texture::{TextureId, TextureLoader},
+        Rendering2D,
+    },
+};
 use bullets::*;
 use cgmath::{Vector2, Vector4, Zero};
+use data::*;
 use debug::*;
 use enemy::*;
-use image::EncodableLayout;
 use particals::*;
 use player::*;
 use powerups::*;
-use rand::Rng;
-use std::{
-    fs::File,
-    io::{BufReader, Write},
-    path::Path,
-};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use slotmap::{new_key_type, SlotMap};
+use spatial::{SpatialGrid, COLLISION_CELL_SIZE};
+use std::{collections::HashMap, path::Path};
+use ui::*;
 use waves::*;
 
 mod bullets;
+mod data;
 mod debug;
 mod enemy;
+mod events;
 mod particals;
 mod player;
 mod powerups;
+mod score;
+pub mod spatial;
+mod ui;
 mod waves;
 
+pub use bullets::BulletStats;
+pub(crate) use data::load_definition;
+pub use data::DataError;
+pub use events::{DamageSource, GameEvent};
+pub use score::{KillCause, Score};
+
+pub const DEFAULT_SHIP: &str = "V1";
+
+/// How much of the world fits in the height of the window.
+pub const VIEW_HEIGHT: f32 = 1000.0;
+
+/// The only source of randomness in the simulation, so a seed reproduces a whole game.
+pub type GameRng = ChaCha8Rng;
+
+/// A partical emitter seen as an engine, for sounds that follow it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Engine {
+    pub pos: Vector2<f32>,
+    /// How hard it is pushing compared to its usual speed, so 1 normally and 0 when stopped.
+    pub throttle: f32,
+}
+
+/// Particals for the GPU to take over, from `Game::take_new_particals`.
+pub struct NewParticals {
+    pub spawns: Vec<ParticalSpawn>,
+    /// Game time the particals already on the GPU are behind by.
+    pub dt: f32,
+    /// Whether the GPU still has particals from an earlier game to get rid of.
+    pub clear: bool,
+}
+
+/// Everything a game is built from that has to be loaded, so restarting does not touch the disk
+/// or the renderer.
+pub struct Assets {
+    ships: Vec<Player>,
+    enemies: Vec<Enemy>,
+    effects: Effects,
+    repair_texture: TextureId,
+    enemy_warning_image: TextureId,
+}
+
 pub struct Game {
-    camera_pos: Vector2<f32>,
-    texture: TextureId,
+    seed: u64,
+    rng: GameRng,
+    /// Kept apart from `rng` so effects can't change how the game plays out.
+    effects_rng: GameRng,
+    time: f32,
     player: Player,
     enemies: Vec<Enemy>,
-    bullets: Vec<Bullet>,
+    bullets: BulletPool,
     particals: Vec<Partical>,
     waves: Vec<Wave>,
     power_ups: Vec<PowerUp>,
     enemy_warning_image: TextureId,
+    effects: Effects,
+    score: Score,
+    /// What happened during the last tick.
+    events: Vec<GameEvent>,
+    /// Game time the particals on the GPU are behind by, since they only move when drawn.
+    gpu_partical_time: f32,
+    /// Whether the GPU still has particals from an earlier game.
+    clear_gpu_particals: bool,
 }
 
 #[derive(Clone)]
 struct Player {
+    name: String,
     pos: Vector2<f32>,
+    prev_pos: Vector2<f32>,
     vel: Vector2<f32>,
     dir: Vector2<f32>,
+    prev_dir: Vector2<f32>,
     speed_original: f32,
     left_turn_original: f32,
     right_turn_original: f32,
@@ -65,24 +133,31 @@ struct Part {
 struct Enemy {
     name: String,
     pos: Vector2<f32>,
+    prev_pos: Vector2<f32>,
     vel: Vector2<f32>,
     dir: Vector2<f32>,
+    prev_dir: Vector2<f32>,
     targetpos: Vector2<f32>,
+    ai: EnemyAi,
     speed: f32,
     turningspeed: f32,
     predictive: bool,
-    texture_scale: f32,
     friction: f32,
     size: f32,
     health: f32,
+    /// What the enemy is worth when destroyed.
+    points: u32,
+    /// What brought its health down to zero first, once it has.
+    killed_by: Option<KillCause>,
     partical_emmiters: Vec<ParticalEmitter>,
     bullet_emmiters: Vec<BulletEmitter>,
     texture_id: TextureId,
-    extra_texture_ids: Vec<TextureId>,
+    turret_texture_id: Option<TextureId>,
 }
 #[derive(Clone)]
 struct Bullet {
     pos: Vector2<f32>,
+    prev_pos: Vector2<f32>,
     vel: Vector2<f32>,
     size: f32,
     damage: f32,
@@ -90,17 +165,70 @@ struct Bullet {
     duration: f32,
     time: f32,
 }
+new_key_type! {
+    struct BulletId;
+}
+
+/// Bullets kept in reusable slots, so a long session recycles the same memory instead of
+/// growing.
+#[derive(Default)]
+struct BulletPool {
+    bullets: SlotMap<BulletId, Bullet>,
+    stats: BulletStats,
+}
+
 #[derive(Clone)]
 struct Partical {
     pos: Vector2<f32>,
+    prev_pos: Vector2<f32>,
     vel: Vector2<f32>,
+    /// The size at the start, growing or shrinking to `ending_size` over its duration.
     size: f32,
+    ending_size: f32,
     shape: ParticalShape,
+    /// In degrees, only used by `RotSquare`.
+    rotation: f32,
+    prev_rotation: f32,
+    /// Degrees per second.
+    angular_vel: f32,
     starting_color: Vector4<f32>,
     ending_color: Vector4<f32>,
     duration: f32,
     time: f32,
 }
+/// The effects the game plays, which `data/effects` has to have.
+const ENEMY_DEATH_EFFECT: &str = "enemy_death";
+const ENEMY_HIT_EFFECT: &str = "enemy_hit";
+const PART_HIT_EFFECT: &str = "part_hit";
+const PART_RAMMED_EFFECT: &str = "part_rammed";
+const EFFECTS_USED: [&str; 4] = [
+    ENEMY_DEATH_EFFECT,
+    ENEMY_HIT_EFFECT,
+    PART_HIT_EFFECT,
+    PART_RAMMED_EFFECT,
+];
+
+/// A burst of particals, like an explosion, described in `data/effects`.
+#[derive(Clone)]
+struct Effect {
+    count: usize,
+    /// How fast the particals fly apart, picked between the two for each one.
+    force: (f32, f32),
+    size: f32,
+    ending_size: f32,
+    shape: ParticalShape,
+    angular_speed: f32,
+    starting_color: Vector4<f32>,
+    ending_color: Vector4<f32>,
+    duration: f32,
+}
+
+/// Every effect by name.
+#[derive(Clone, Default)]
+struct Effects {
+    effects: HashMap<String, Effect>,
+}
+
 #[derive(Clone)]
 struct ParticalEmitter {
     pos: Vector2<f32>,
@@ -115,6 +243,8 @@ struct ParticalEmitter {
     partical_interval: f32,
     time: f32,
     speed: f32,
+    /// The fastest a `RotSquare` partical spins either way, in degrees per second.
+    angular_speed: f32,
 }
 
 #[derive(Clone)]
@@ -125,6 +255,7 @@ struct BulletEmitter {
     damage: f32,
     friendly: bool,
     duration: f32,
+    bullet_interval_original: f32,
     bullet_interval: f32,
     time: f32,
 }
@@ -154,18 +285,19 @@ struct Wave {
     enemy: Enemy,
 }
 
-enum PowerUpType {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUpType {
     Shield,
     Repair,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
 enum DamageType {
     Mult,
     Div,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
 enum PartMod {
     Partical,
     Gun,
@@ -174,83 +306,338 @@ enum PartMod {
     Speed,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+enum EnemyAi {
+    Chase,
+    Orbit { angle: f32, distance: f32 },
+}
+
+#[derive(Clone, Deserialize)]
 enum ParticalShape {
     Square,
     Circle,
     RotSquare,
 }
 
+impl Assets {
+    pub fn load(textures: &mut dyn TextureLoader) -> Result<Self, DataError> {
+        Ok(Self {
+            ships: load_ships(textures)?,
+            enemies: load_enemies(textures)?,
+            effects: load_effects()?,
+            repair_texture: load_image(textures, "repair", Path::new("images/Repair.png"))?,
+            enemy_warning_image: load_image(
+                textures,
+                "enemy_warning_image",
+                Path::new("images/EnemyWarning.png"),
+            )?,
+        })
+    }
+}
+
 impl Game {
-    pub fn new(renderer: &mut Renderer) -> Self {
-        Self {
-            camera_pos: Vector2 { x: 0.0, y: 0.0 },
-            texture: renderer.create_texture("Yellow", 1, 1, &[255, 255, 0, 255]),
-            player: init_player(renderer),
+    pub fn new(assets: &Assets, ship: &str, seed: u64) -> Result<Self, DataError> {
+        Ok(Self {
+            seed,
+            rng: GameRng::seed_from_u64(seed),
+            effects_rng: effects_rng(seed),
+            time: 0.0,
+            player: find_ship(&assets.ships, ship)?.clone(),
             enemies: vec![],
-            bullets: vec![],
+            bullets: BulletPool::default(),
             particals: vec![],
-            waves: init_waves(renderer),
+            waves: init_waves(&assets.enemies)?,
             power_ups: vec![PowerUp {
                 pos: Vector2::zero(),
                 power_type: PowerUpType::Repair,
-                texture: load_texture(renderer, "repair", Path::new("images/Repair.png")),
+                texture: assets.repair_texture,
             }],
-            enemy_warning_image: load_texture(
-                renderer,
-                "enemy_warning_image",
-                Path::new("images/EnemyWarning.png"),
-            ),
-        }
+            enemy_warning_image: assets.enemy_warning_image,
+            effects: assets.effects.clone(),
+            score: Score::default(),
+            events: vec![],
+            gpu_partical_time: 0.0,
+            clear_gpu_particals: true,
+        })
     }
 
-    pub fn update(&mut self, dt: f32) {
-        //print!("\r{}", 1.0 / dt);
-        //std::io::stdout().flush().unwrap();
-        self.camera_pos = self.player.pos;
+    pub fn update(&mut self, dt: f32, input: PlayerInput) {
+        self.time += dt;
+        self.gpu_partical_time += dt;
+        self.score.update(dt);
+        self.events.clear();
+        self.store_previous_positions();
         update_player(
             &mut self.player,
+            input,
             &mut self.enemies,
             &mut self.bullets,
             &mut self.particals,
+            &mut self.events,
+            &mut self.rng,
+            dt,
+        );
+        update_waves(
+            &mut self.waves,
+            &self.player,
+            &mut self.enemies,
+            &mut self.events,
+            &mut self.rng,
             dt,
         );
-        update_waves(&mut self.waves, &self.player, &mut self.enemies, dt);
         update_enemies(
             &mut self.player,
             &mut self.enemies,
             &mut self.particals,
             &mut self.bullets,
+            &mut self.events,
+            &mut self.rng,
             dt,
         );
         update_bullets(
             &mut self.player,
             &mut self.bullets,
             &mut self.enemies,
-            &mut self.particals,
+            &mut self.events,
             dt,
         );
         update_particals(&mut self.particals, dt);
+        update_power_ups(
+            &mut self.player,
+            &mut self.power_ups,
+            &mut self.events,
+            &mut self.rng,
+        );
+        self.handle_events();
+    }
+
+    /// The score and the effects, reacting to what happened this tick.
+    fn handle_events(&mut self) {
+        for event in &self.events {
+            match *event {
+                GameEvent::EnemyKilled {
+                    pos,
+                    vel,
+                    points,
+                    cause,
+                    ..
+                } => {
+                    self.score.record_kill(points, cause);
+                    self.effects.play(
+                        ENEMY_DEATH_EFFECT,
+                        &mut self.particals,
+                        &mut self.effects_rng,
+                        pos,
+                        vel,
+                    );
+                }
+                GameEvent::EnemyHit { pos } => self.effects.play(
+                    ENEMY_HIT_EFFECT,
+                    &mut self.particals,
+                    &mut self.effects_rng,
+                    pos,
+                    self.player.vel,
+                ),
+                GameEvent::PartDamaged { pos, source, .. } => {
+                    let effect = match source {
+                        DamageSource::Bullet => PART_HIT_EFFECT,
+                        DamageSource::Rammed => PART_RAMMED_EFFECT,
+                    };
+                    self.effects.play(
+                        effect,
+                        &mut self.particals,
+                        &mut self.effects_rng,
+                        pos,
+                        self.player.vel,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// What happened during the last call to `update`.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Remembers where everything was before this tick, for interpolating between ticks when
+    /// rendering.
+    fn store_previous_positions(&mut self) {
+        self.player.prev_pos = self.player.pos;
+        self.player.prev_dir = self.player.dir;
+        for enemy in &mut self.enemies {
+            enemy.prev_pos = enemy.pos;
+            enemy.prev_dir = enemy.dir;
+        }
+        for bullet in self.bullets.iter_mut() {
+            bullet.prev_pos = bullet.pos;
+        }
+        for partical in &mut self.particals {
+            partical.prev_pos = partical.pos;
+            partical.prev_rotation = partical.rotation;
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn enemy_count(&self) -> usize {
+        self.enemies.len()
+    }
+
+    pub fn bullet_count(&self) -> usize {
+        self.bullets.stats().live
+    }
+
+    pub fn bullet_stats(&self) -> BulletStats {
+        self.bullets.stats()
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn partical_count(&self) -> usize {
+        self.particals.len()
+    }
+
+    /// The game ends once every part of the player's ship is destroyed.
+    pub fn is_over(&self) -> bool {
+        self.player.parts.iter().all(|part| part.health <= 0.0)
+    }
+
+    /// Remaining health of all the player's parts combined, from 0 to 1.
+    pub fn player_health(&self) -> f32 {
+        let health: f32 = self
+            .player
+            .parts
+            .iter()
+            .map(|part| part.health.max(0.0))
+            .sum();
+        let starting_health: f32 = self
+            .player
+            .parts
+            .iter()
+            .map(|part| part.starting_health)
+            .sum();
+        health / starting_health
+    }
+
+    /// Every partical emitter on the player and the enemies.
+    pub fn engines(&self) -> Vec<Engine> {
+        let enemy_emitters = self
+            .enemies
+            .iter()
+            .flat_map(|enemy| &enemy.partical_emmiters);
+        self.player
+            .partical_emmiters
+            .iter()
+            .chain(enemy_emitters)
+            .map(|emitter| Engine {
+                pos: emitter.pos,
+                throttle: if emitter.speed_orginal > 0.0 {
+                    emitter.speed / emitter.speed_orginal
+                } else {
+                    0.0
+                },
+            })
+            .collect()
+    }
+
+    /// The camera follows the player. `alpha` is how far the frame is between the previous tick
+    /// and the latest one, from 0 to 1.
+    pub fn camera_position(&self, alpha: f32) -> Vector2<f32> {
+        interpolate(self.player.prev_pos, self.player.pos, alpha)
+    }
+
+    /// The timer and the health of each part, drawn over the world.
+    pub fn draw_hud(&self, drawing: &mut Rendering2D<'_, '_>) {
+        draw_timer(drawing, self.time);
+        draw_score(drawing, &self.score);
+        draw_part_health(drawing, &self.player);
+    }
+
+    /// Hitboxes, where enemies are heading and the numbers behind the player's ship.
+    pub fn draw_debug(&self, drawing: &mut Rendering2D<'_, '_>, alpha: f32, fps: f32) {
+        draw_debug_enemies(drawing, &self.enemies, alpha);
+        draw_debug_player(drawing, &self.player, alpha);
+        draw_debug_text(
+            drawing,
+            &self.player,
+            &self.enemies,
+            drawing.gpu_partical_count().unwrap_or(self.particals.len()),
+            &self.bullets,
+            fps,
+        );
+    }
+
+    /// Hands the particals made since the last call over to the GPU, which simulates them from
+    /// then on. Only for when the GPU simulates particals, otherwise `draw` draws them.
+    pub fn take_new_particals(&mut self) -> NewParticals {
+        NewParticals {
+            spawns: self.particals.drain(..).map(|p| p.spawn()).collect(),
+            dt: std::mem::take(&mut self.gpu_partical_time),
+            clear: std::mem::take(&mut self.clear_gpu_particals),
+        }
     }
 
-    pub fn render(&mut self, frame: &mut FrameRendering<'_>) {
-        let mut drawing = Rendering2D::new(frame, self.camera_pos, 1000.0);
-        power_ups_update(&mut drawing, &mut self.player, &mut self.power_ups);
-        draw_player(&mut drawing, &self.player, self.player.texture_id);
+    pub fn draw(&self, drawing: &mut Rendering2D<'_, '_>, alpha: f32) {
+        draw_power_ups(drawing, &self.power_ups);
+        draw_player(drawing, &self.player, self.player.texture_id, alpha);
         draw_enemies(
-            &mut drawing,
+            drawing,
             &self.player,
             &self.enemies,
             &self.enemy_warning_image,
+            alpha,
         );
-        draw_particals(&mut drawing, &mut self.particals);
-        draw_bullets(&mut drawing, &mut self.bullets);
+        if !drawing.has_gpu_particals() {
+            draw_particals(drawing, &self.particals, alpha);
+        }
+        draw_bullets(drawing, &self.bullets, alpha);
     }
 }
 
-fn load_texture(renderer: &mut Renderer, name: &str, path: &Path) -> TextureId {
-    let image = image::open(path).unwrap().flipv().into_rgba8();
-    renderer.create_texture(name, image.width(), image.height(), image.as_bytes())
+#[cfg(test)]
+fn test_assets() -> Assets {
+    Assets::load(&mut crate::renderer::texture::HeadlessTextures::default()).unwrap()
+}
+
+fn load_image(
+    textures: &mut dyn TextureLoader,
+    name: &str,
+    path: &Path,
+) -> Result<TextureId, DataError> {
+    textures
+        .load_texture(name, path)
+        .map_err(|error| DataError::new(path, "", error))
+}
+
+fn enemy_grid(enemies: &[Enemy]) -> SpatialGrid<usize> {
+    SpatialGrid::new(
+        COLLISION_CELL_SIZE,
+        enemies
+            .iter()
+            .enumerate()
+            .map(|(index, enemy)| (index, enemy.pos, enemy.size)),
+    )
+}
+
+fn part_grid(player: &Player) -> SpatialGrid<usize> {
+    SpatialGrid::new(
+        COLLISION_CELL_SIZE,
+        player
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(index, part)| (index, part.pos, part.size)),
+    )
 }
 
 fn get_2_mut<T>(xs: &mut [T], a: usize, b: usize) -> Option<(&mut T, &mut T)> {
@@ -263,13 +650,17 @@ fn get_2_mut<T>(xs: &mut [T], a: usize, b: usize) -> Option<(&mut T, &mut T)> {
 
 fn colorlerp(starting_color: Vector4<f32>, ending_color: Vector4<f32>, t: f32) -> Vector4<f32> {
     Vector4::new(
-        starting_color.x as f32 + (ending_color.x as f32 - starting_color.x as f32) * t,
-        starting_color.y as f32 + (ending_color.y as f32 - starting_color.y as f32) * t,
-        starting_color.z as f32 + (ending_color.z as f32 - starting_color.z as f32) * t,
-        starting_color.w as f32 + (ending_color.w as f32 - starting_color.w as f32) * t,
+        starting_color.x + (ending_color.x - starting_color.x) * t,
+        starting_color.y + (ending_color.y - starting_color.y) * t,
+        starting_color.z + (ending_color.z - starting_color.z) * t,
+        starting_color.w + (ending_color.w - starting_color.w) * t,
     )
 }
 
+fn interpolate(previous: Vector2<f32>, current: Vector2<f32>, alpha: f32) -> Vector2<f32> {
+    previous + (current - previous) * alpha
+}
+
 fn vectortoangle(vector: Vector2<f32>) -> f32 {
     f32::atan2(vector.y, vector.x)
 }
@@ -278,6 +669,13 @@ fn angletovector(angle: f32) -> Vector2<f32> {
     Vector2::new(f32::cos(angle), f32::sin(angle))
 }
 
+/// A separate stream of the same seed, so games with the same seed also look the same.
+fn effects_rng(seed: u64) -> GameRng {
+    let mut rng = GameRng::seed_from_u64(seed);
+    rng.set_stream(1);
+    rng
+}
+
 fn rotatevector(vector: Vector2<f32>, angle: f32) -> Vector2<f32> {
     Vector2 {
         x: vector.x * f32::cos(angle) - vector.y * f32::sin(angle),
@@ -285,54 +683,62 @@ fn rotatevector(vector: Vector2<f32>, angle: f32) -> Vector2<f32> {
     }
 }
 
-fn enemy_dies(pos: Vector2<f32>, vel: Vector2<f32>, particals: &mut Vec<Partical>) {
-    particalexplosion(
-        particals,
-        pos,
-        vel,
-        0.0,
-        300.0,
-        500,
-        Vector4 {
-            x: 200.0 / 255.0,
-            y: 200.0 / 255.0,
-            z: 50.0 / 255.0,
-            w: 255.0 / 255.0,
-        },
-        Vector4 {
-            x: 255.0 / 255.0,
-            y: 0.0 / 255.0,
-            z: 0.0 / 255.0,
-            w: 100.0 / 255.0,
-        },
-        0.3,
-    );
-}
-
-fn particalexplosion(
-    particals: &mut Vec<Partical>,
-    pos: Vector2<f32>,
-    vel: Vector2<f32>,
-    force_min: f32,
-    force_max: f32,
-    amount: usize,
-    start_color: Vector4<f32>,
-    ending_color: Vector4<f32>,
-    duration: f32,
-) {
-    for _ in 0..amount {
-        particals.push(Partical {
-            pos: pos,
-            vel: vel
-                + angletovector(
-                    rand::thread_rng().gen_range(-std::f32::consts::PI..std::f32::consts::PI),
-                ) * rand::thread_rng().gen_range(force_min..force_max),
-            size: 5.0,
-            shape: ParticalShape::Square,
-            starting_color: start_color,
-            ending_color: ending_color,
-            duration: duration,
-            time: 0.0,
-        });
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What can be seen of a game after one tick.
+    #[derive(Debug, PartialEq)]
+    struct Snapshot {
+        events: Vec<GameEvent>,
+        player_pos: Vector2<f32>,
+        player_dir: Vector2<f32>,
+        health: Vec<f32>,
+        enemies: Vec<Vector2<f32>>,
+        bullets: usize,
+        score: u64,
+    }
+
+    /// Plays `ticks` ticks of a game with inputs that only depend on the tick.
+    fn play(assets: &Assets, seed: u64, ticks: u32) -> Vec<Snapshot> {
+        let mut game = Game::new(assets, "V1", seed).unwrap();
+        (0..ticks)
+            .map(|tick| {
+                let turn = (tick / 90 % 3) as f32 / 2.0;
+                game.update(
+                    1.0 / 60.0,
+                    PlayerInput {
+                        turn_left: turn,
+                        turn_right: 1.0 - turn,
+                        thrust: if tick / 120 % 3 == 2 { 0.0 } else { 1.0 },
+                        fire: tick / 30 % 2 == 0,
+                    },
+                );
+                Snapshot {
+                    events: game.events().to_vec(),
+                    player_pos: game.player.pos,
+                    player_dir: game.player.dir,
+                    health: game.player.parts.iter().map(|part| part.health).collect(),
+                    enemies: game.enemies.iter().map(|enemy| enemy.pos).collect(),
+                    bullets: game.bullet_count(),
+                    score: game.score().total(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_and_inputs_play_the_same() {
+        let assets = test_assets();
+        let first = play(&assets, 7, 3600);
+        assert!(first.iter().any(|snapshot| !snapshot.enemies.is_empty()));
+        assert!(first.iter().any(|snapshot| !snapshot.events.is_empty()));
+        assert!(first == play(&assets, 7, 3600));
+    }
+
+    #[test]
+    fn different_seeds_play_differently() {
+        let assets = test_assets();
+        assert!(play(&assets, 7, 3600) != play(&assets, 8, 3600));
     }
 }
@@ -1,90 +1,130 @@
-use cgmath::{InnerSpace, MetricSpace, Vector2, Vector4};
+use cgmath::{Vector2, Vector4};
 
-use crate::renderer::Rendering2D;
+use crate::renderer::{Layer, Rendering2D};
 
-use super::{particalexplosion, vectortoangle, Bullet, Enemy, Partical, Player};
+use super::{
+    damage_part, enemy_grid, interpolate, part_grid, vectortoangle, Bullet, BulletPool,
+    DamageSource, Enemy, GameEvent, KillCause, Player,
+};
 
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BulletStats {
+    /// Bullets in flight right now.
+    pub live: usize,
+    /// The most bullets that have been in flight at once.
+    pub peak: usize,
+    pub fired: u64,
+    pub expired: u64,
+}
+
+impl BulletPool {
+    pub fn fire(&mut self, bullet: Bullet) {
+        self.bullets.insert(bullet);
+        self.stats.fired += 1;
+        self.stats.peak = self.stats.peak.max(self.bullets.len());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Bullet> {
+        self.bullets.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Bullet> {
+        self.bullets.values_mut()
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Bullet) -> bool) {
+        self.bullets.retain(|_, bullet| keep(bullet));
+    }
+
+    /// Removes the bullets that have outlived their duration.
+    fn expire(&mut self) {
+        let live = self.bullets.len();
+        self.bullets
+            .retain(|_, bullet| bullet.time < bullet.duration);
+        self.stats.expired += (live - self.bullets.len()) as u64;
+    }
+
+    pub fn stats(&self) -> BulletStats {
+        BulletStats {
+            live: self.bullets.len(),
+            ..self.stats
+        }
+    }
+}
 
-pub fn update_bullets(player: &mut Player, bullets: &mut Vec<Bullet>, enemies: &mut Vec<Enemy>,particals: &mut Vec<Partical>,dt:f32) {
-    for bullet in bullets {
+/// How much of its lifetime a bullet has left, from 1 when fired to 0 when it expires. Damage
+/// and size both fade with it.
+fn remaining_life(bullet: &Bullet) -> f32 {
+    (1.0 - bullet.time / bullet.duration).max(0.0)
+}
+
+pub fn update_bullets(
+    player: &mut Player,
+    bullets: &mut BulletPool,
+    enemies: &mut [Enemy],
+    events: &mut Vec<GameEvent>,
+    dt: f32,
+) {
+    for bullet in bullets.iter_mut() {
         bullet.pos += bullet.vel * dt;
         bullet.time += dt;
+    }
+    bullets.expire();
+    let enemy_grid = enemy_grid(enemies);
+    let part_grid = part_grid(player);
+    for bullet in bullets.iter() {
         if bullet.friendly {
-            for enemy in enemies.iter_mut() {
-                if bullet.pos.distance(enemy.pos) < bullet.size * 2.0 + enemy.size {
-                    enemy.health -=
-                        bullet.damage - bullet.time / bullet.duration * bullet.damage;
-                    particalexplosion(
-                        particals,
-                        bullet.pos,
-                        player.vel,
-                        0.0,
-                        600.0,
-                        50,
-                        Vector4 {
-                            x: 255.0 / 255.0,
-                            y: 0.0 / 255.0,
-                            z: 0.0 / 255.0,
-                            w: 255.0 / 255.0,
-                        },
-                        Vector4 {
-                            x: 255.0 / 255.0,
-                            y: 255.0 / 255.0,
-                            z: 50.0 / 255.0,
-                            w: 0.0 / 255.0,
-                        },
-                        0.1,
-                    );
-                }
+            for enemy_index in enemy_grid.overlapping(bullet.pos, bullet.size * 2.0) {
+                let enemy = &mut enemies[enemy_index];
+                enemy.damage(bullet.damage * remaining_life(bullet), KillCause::Bullet);
+                events.push(GameEvent::EnemyHit { pos: bullet.pos });
             }
         }
         if !bullet.friendly {
-            for part in &mut player.parts {
-                if bullet.pos.distance(part.pos) < bullet.size * 2.0 + part.size {
-                    part.health -=
-                        bullet.damage - bullet.time / bullet.duration * bullet.damage;
-                    particalexplosion(
-                        particals,
-                        bullet.pos,
-                        player.vel,
-                        0.0,
-                        600.0,
-                        50,
-                        Vector4 {
-                            x: 140.0 / 255.0,
-                            y: 255.0 / 255.0,
-                            z: 251.0 / 255.0,
-                            w: 255.0 / 255.0,
-                        },
-                        Vector4 {
-                            x: 255.0 / 255.0,
-                            y: 0.0 / 255.0,
-                            z: 50.0 / 255.0,
-                            w: 0.0 / 255.0,
-                        },
-                        0.1,
-                    )
-                }
+            for part_index in part_grid.overlapping(bullet.pos, bullet.size * 2.0) {
+                let damage = bullet.damage * remaining_life(bullet);
+                damage_part(
+                    player,
+                    part_index,
+                    damage,
+                    DamageSource::Bullet,
+                    bullet.pos,
+                    events,
+                );
             }
         }
     }
 }
 
-pub fn draw_bullets(drawing:&mut Rendering2D<'_,'_>,bullets: &mut Vec<Bullet>) {
-    for bullet in bullets {
-        let bullet_scale = 1.0 - bullet.time / bullet.duration;
+pub fn draw_bullets(drawing: &mut Rendering2D<'_, '_>, bullets: &BulletPool, alpha: f32) {
+    for bullet in bullets.iter() {
+        let bullet_scale = remaining_life(bullet);
         let bullet_width = bullet.size * bullet_scale;
         let bullet_length = bullet.size * 2.0 * bullet_scale;
-        let mut color = Vector4 { x: 0.0, y: 1.0, z: 0.0, w: 1.0 };
+        let mut color = Vector4 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+            w: 1.0,
+        };
         if !bullet.friendly {
-            color = Vector4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 }
+            color = Vector4 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+                w: 1.0,
+            }
         }
         drawing.draw_quad(
-            bullet.pos,
-            Vector2 { x: bullet_width, y: bullet_length },
+            interpolate(bullet.prev_pos, bullet.pos, alpha),
+            Vector2 {
+                x: bullet_width,
+                y: bullet_length,
+            },
             color,
             vectortoangle(bullet.vel).to_degrees() - 90.0,
             None,
+            Layer::Bullets,
         );
         //d.draw_rectangle_pro(
         //    Rectangle::new(
@@ -98,4 +138,75 @@ pub fn draw_bullets(drawing:&mut Rendering2D<'_,'_>,bullets: &mut Vec<Bullet>) {
         //    color,
         //)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_assets;
+
+    fn bullet(duration: f32) -> Bullet {
+        let pos = Vector2::new(100000.0, 100000.0);
+        Bullet {
+            pos,
+            prev_pos: pos,
+            vel: Vector2::new(100.0, 0.0),
+            size: 5.0,
+            damage: 1.0,
+            friendly: true,
+            duration,
+            time: 0.0,
+        }
+    }
+
+    /// Moves the bullets on `ticks` ticks of a sixtieth of a second, far from the player.
+    fn run(bullets: &mut BulletPool, ticks: u32) {
+        let mut player = test_assets().ships[0].clone();
+        for _ in 0..ticks {
+            update_bullets(&mut player, bullets, &mut [], &mut vec![], 1.0 / 60.0);
+        }
+    }
+
+    #[test]
+    fn expires_after_duration() {
+        let mut bullets = BulletPool::default();
+        bullets.fire(bullet(1.0));
+        bullets.fire(bullet(0.5));
+        run(&mut bullets, 45);
+        assert_eq!(bullets.stats().live, 1);
+        run(&mut bullets, 20);
+        assert_eq!(bullets.stats().live, 0);
+    }
+
+    #[test]
+    fn reuses_slots() {
+        let mut bullets = BulletPool::default();
+        for _ in 0..100 {
+            bullets.fire(bullet(0.1));
+        }
+        let capacity = bullets.bullets.capacity();
+        for _ in 0..10 {
+            run(&mut bullets, 10);
+            for _ in 0..100 {
+                bullets.fire(bullet(0.1));
+            }
+        }
+        assert_eq!(bullets.bullets.capacity(), capacity);
+    }
+
+    #[test]
+    fn counts() {
+        let mut bullets = BulletPool::default();
+        for _ in 0..3 {
+            bullets.fire(bullet(0.1));
+        }
+        run(&mut bullets, 10);
+        bullets.fire(bullet(1.0));
+        bullets.fire(bullet(1.0));
+        let stats = bullets.stats();
+        assert_eq!(
+            (stats.live, stats.peak, stats.fired, stats.expired),
+            (2, 3, 5, 3)
+        );
+    }
+}
@@ -0,0 +1,767 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use cgmath::{Vector2, Vector4, Zero};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::renderer::texture::{TextureId, TextureLoader};
+
+use super::{
+    BulletEmitter, Damage, DamageType, Effect, Effects, Enemy, EnemyAi, Part, PartMod,
+    ParticalEmitter, ParticalShape, Player, EFFECTS_USED,
+};
+
+pub const ENEMIES_DIRECTORY: &str = "data/enemies";
+pub const SHIPS_DIRECTORY: &str = "data/ships";
+pub const EFFECTS_DIRECTORY: &str = "data/effects";
+
+#[derive(Debug)]
+pub struct DataError {
+    pub path: PathBuf,
+    pub field: String,
+    pub message: String,
+}
+
+impl DataError {
+    pub fn new(path: impl Into<PathBuf>, field: impl Into<String>, message: impl ToString) -> Self {
+        Self {
+            path: path.into(),
+            field: field.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}: {}", self.path.display(), self.message)
+        } else {
+            write!(
+                f,
+                "{}: `{}`: {}",
+                self.path.display(),
+                self.field,
+                self.message
+            )
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+type Color = (u8, u8, u8, u8);
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnemyDefinition {
+    name: String,
+    ai: EnemyAi,
+    #[serde(default)]
+    predictive: bool,
+    texture: PathBuf,
+    #[serde(default)]
+    turret_texture: Option<PathBuf>,
+    speed: f32,
+    turning_speed: f32,
+    /// Not used for drawing any more, but still in every enemy file.
+    texture_scale: f32,
+    friction: f32,
+    size: f32,
+    health: f32,
+    points: u32,
+    #[serde(default)]
+    partical_emitters: Vec<ParticalEmitterDefinition>,
+    #[serde(default)]
+    bullet_emitters: Vec<BulletEmitterDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShipDefinition {
+    name: String,
+    texture: PathBuf,
+    speed: f32,
+    left_turn: f32,
+    right_turn: f32,
+    parts: Vec<PartDefinition>,
+    #[serde(default)]
+    damage: Vec<DamageDefinition>,
+    #[serde(default)]
+    partical_emitters: Vec<ParticalEmitterDefinition>,
+    #[serde(default)]
+    bullet_emitters: Vec<BulletEmitterDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PartDefinition {
+    name: String,
+    location: (f32, f32),
+    health: f32,
+    size: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DamageDefinition {
+    src: Vec<usize>,
+    des: PartMod,
+    #[serde(default)]
+    index: usize,
+    #[serde(default = "default_damage_type")]
+    damage_type: DamageType,
+    #[serde(default = "default_damage_scale")]
+    scale: f32,
+}
+
+fn default_damage_type() -> DamageType {
+    DamageType::Mult
+}
+
+fn default_damage_scale() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParticalEmitterDefinition {
+    location: (f32, f32),
+    speed: f32,
+    size: f32,
+    shape: ParticalShape,
+    starting_color: Color,
+    ending_color: Color,
+    duration: f32,
+    particals_per_second: f32,
+    /// How fast `RotSquare` particals spin at most, in degrees per second.
+    #[serde(default)]
+    angular_speed: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EffectDefinition {
+    name: String,
+    count: usize,
+    force: (f32, f32),
+    size: f32,
+    /// Defaults to `size`, for particals that stay the same size.
+    #[serde(default)]
+    ending_size: Option<f32>,
+    #[serde(default = "default_effect_shape")]
+    shape: ParticalShape,
+    #[serde(default)]
+    angular_speed: f32,
+    starting_color: Color,
+    ending_color: Color,
+    duration: f32,
+}
+
+fn default_effect_shape() -> ParticalShape {
+    ParticalShape::Square
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BulletEmitterDefinition {
+    location: (f32, f32),
+    size: f32,
+    damage: f32,
+    duration: f32,
+    bullets_per_second: f32,
+    #[serde(default)]
+    delay: f32,
+}
+
+pub fn load_enemies(textures: &mut dyn TextureLoader) -> Result<Vec<Enemy>, DataError> {
+    let mut enemies: Vec<Enemy> = vec![];
+    for path in definition_paths(Path::new(ENEMIES_DIRECTORY))? {
+        let definition: EnemyDefinition = load_definition(&path)?;
+        definition.validate(&path)?;
+        if enemies.iter().any(|enemy| enemy.name == definition.name) {
+            return Err(DataError::new(
+                &path,
+                "name",
+                format!("enemy `{}` is defined more than once", definition.name),
+            ));
+        }
+        enemies.push(definition.build(&path, textures)?);
+    }
+    Ok(enemies)
+}
+
+pub fn load_ships(textures: &mut dyn TextureLoader) -> Result<Vec<Player>, DataError> {
+    let mut ships: Vec<Player> = vec![];
+    for path in definition_paths(Path::new(SHIPS_DIRECTORY))? {
+        let definition: ShipDefinition = load_definition(&path)?;
+        definition.validate(&path)?;
+        if ships.iter().any(|ship| ship.name == definition.name) {
+            return Err(DataError::new(
+                &path,
+                "name",
+                format!("ship `{}` is defined more than once", definition.name),
+            ));
+        }
+        ships.push(definition.build(&path, textures)?);
+    }
+    Ok(ships)
+}
+
+/// Loads every effect, making sure the ones the game plays are there.
+pub fn load_effects() -> Result<Effects, DataError> {
+    let mut effects = Effects::default();
+    for path in definition_paths(Path::new(EFFECTS_DIRECTORY))? {
+        let definition: EffectDefinition = load_definition(&path)?;
+        definition.validate(&path)?;
+        if effects.effects.contains_key(&definition.name) {
+            return Err(DataError::new(
+                &path,
+                "name",
+                format!("effect `{}` is defined more than once", definition.name),
+            ));
+        }
+        effects
+            .effects
+            .insert(definition.name.clone(), definition.build());
+    }
+    for name in EFFECTS_USED {
+        if !effects.effects.contains_key(name) {
+            return Err(DataError::new(
+                EFFECTS_DIRECTORY,
+                "",
+                format!("no effect named `{name}`"),
+            ));
+        }
+    }
+    Ok(effects)
+}
+
+pub fn find_ship<'a>(ships: &'a [Player], name: &str) -> Result<&'a Player, DataError> {
+    ships
+        .iter()
+        .find(|ship| ship.name == name)
+        .ok_or_else(|| DataError::new(SHIPS_DIRECTORY, "", format!("no ship named `{name}`")))
+}
+
+pub fn find_enemy<'a>(enemies: &'a [Enemy], name: &str) -> Result<&'a Enemy, DataError> {
+    enemies
+        .iter()
+        .find(|enemy| enemy.name == name)
+        .ok_or_else(|| DataError::new(ENEMIES_DIRECTORY, "", format!("no enemy named `{name}`")))
+}
+
+fn definition_paths(directory: &Path) -> Result<Vec<PathBuf>, DataError> {
+    let entries = fs::read_dir(directory).map_err(|error| DataError::new(directory, "", error))?;
+    let mut paths = vec![];
+    for entry in entries {
+        let path = entry
+            .map_err(|error| DataError::new(directory, "", error))?
+            .path();
+        if path.extension().is_some_and(|extension| extension == "ron") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+pub fn load_definition<T: DeserializeOwned>(path: &Path) -> Result<T, DataError> {
+    let source = fs::read_to_string(path).map_err(|error| DataError::new(path, "", error))?;
+    let mut deserializer =
+        ron::Deserializer::from_str(&source).map_err(|error| DataError::new(path, "", error))?;
+    let definition = serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+        let field = error.path().to_string();
+        let message = deserializer.span_error(error.into_inner());
+        DataError::new(
+            path,
+            if field == "." { String::new() } else { field },
+            message,
+        )
+    })?;
+    deserializer
+        .end()
+        .map_err(|error| DataError::new(path, "", deserializer.span_error(error)))?;
+    Ok(definition)
+}
+
+fn check_positive(path: &Path, field: impl Into<String>, value: f32) -> Result<(), DataError> {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(DataError::new(
+            path,
+            field,
+            format!("must be greater than zero, got {value}"),
+        ))
+    }
+}
+
+fn check_not_negative(path: &Path, field: impl Into<String>, value: f32) -> Result<(), DataError> {
+    if value >= 0.0 {
+        Ok(())
+    } else {
+        Err(DataError::new(
+            path,
+            field,
+            format!("must not be negative, got {value}"),
+        ))
+    }
+}
+
+fn load_definition_texture(
+    textures: &mut dyn TextureLoader,
+    path: &Path,
+    field: &str,
+    texture: &Path,
+) -> Result<TextureId, DataError> {
+    textures
+        .load_texture(&texture.display().to_string(), texture)
+        .map_err(|error| {
+            DataError::new(
+                path,
+                field,
+                format!("failed to load texture `{}`: {error}", texture.display()),
+            )
+        })
+}
+
+fn color(color: Color) -> Vector4<f32> {
+    Vector4 {
+        x: color.0 as f32 / 255.0,
+        y: color.1 as f32 / 255.0,
+        z: color.2 as f32 / 255.0,
+        w: color.3 as f32 / 255.0,
+    }
+}
+
+impl EnemyDefinition {
+    fn validate(&self, path: &Path) -> Result<(), DataError> {
+        if self.name.is_empty() {
+            return Err(DataError::new(path, "name", "must not be empty"));
+        }
+        if let EnemyAi::Orbit { distance, .. } = self.ai {
+            check_not_negative(path, "ai.distance", distance)?;
+        }
+        check_positive(path, "speed", self.speed)?;
+        check_not_negative(path, "turning_speed", self.turning_speed)?;
+        check_positive(path, "texture_scale", self.texture_scale)?;
+        check_not_negative(path, "friction", self.friction)?;
+        check_positive(path, "size", self.size)?;
+        check_positive(path, "health", self.health)?;
+        for (index, emitter) in self.partical_emitters.iter().enumerate() {
+            emitter.validate(path, &format!("partical_emitters[{index}]"))?;
+        }
+        for (index, emitter) in self.bullet_emitters.iter().enumerate() {
+            emitter.validate(path, &format!("bullet_emitters[{index}]"))?;
+        }
+        Ok(())
+    }
+
+    fn build(self, path: &Path, textures: &mut dyn TextureLoader) -> Result<Enemy, DataError> {
+        let texture_id = load_definition_texture(textures, path, "texture", &self.texture)?;
+        let turret_texture_id = match &self.turret_texture {
+            Some(texture) => Some(load_definition_texture(
+                textures,
+                path,
+                "turret_texture",
+                texture,
+            )?),
+            None => None,
+        };
+        Ok(Enemy {
+            name: self.name,
+            pos: Vector2::zero(),
+            prev_pos: Vector2::zero(),
+            vel: Vector2 { x: 0.0, y: 1.0 },
+            dir: Vector2::zero(),
+            prev_dir: Vector2::zero(),
+            targetpos: Vector2 { x: 200.0, y: 200.0 },
+            ai: self.ai,
+            speed: self.speed,
+            turningspeed: self.turning_speed,
+            predictive: self.predictive,
+            friction: self.friction,
+            size: self.size,
+            health: self.health,
+            points: self.points,
+            killed_by: None,
+            partical_emmiters: self
+                .partical_emitters
+                .iter()
+                .map(ParticalEmitterDefinition::build)
+                .collect(),
+            bullet_emmiters: self
+                .bullet_emitters
+                .iter()
+                .map(|emitter| emitter.build(false))
+                .collect(),
+            texture_id,
+            turret_texture_id,
+        })
+    }
+}
+
+impl ShipDefinition {
+    fn validate(&self, path: &Path) -> Result<(), DataError> {
+        if self.name.is_empty() {
+            return Err(DataError::new(path, "name", "must not be empty"));
+        }
+        check_positive(path, "speed", self.speed)?;
+        check_not_negative(path, "left_turn", self.left_turn)?;
+        check_not_negative(path, "right_turn", self.right_turn)?;
+        if self.parts.is_empty() {
+            return Err(DataError::new(
+                path,
+                "parts",
+                "a ship needs at least one part",
+            ));
+        }
+        for (index, part) in self.parts.iter().enumerate() {
+            part.validate(path, &format!("parts[{index}]"))?;
+        }
+        for (index, damage) in self.damage.iter().enumerate() {
+            damage.validate(self, path, &format!("damage[{index}]"))?;
+        }
+        for (index, emitter) in self.partical_emitters.iter().enumerate() {
+            emitter.validate(path, &format!("partical_emitters[{index}]"))?;
+        }
+        for (index, emitter) in self.bullet_emitters.iter().enumerate() {
+            emitter.validate(path, &format!("bullet_emitters[{index}]"))?;
+        }
+        Ok(())
+    }
+
+    fn build(self, path: &Path, textures: &mut dyn TextureLoader) -> Result<Player, DataError> {
+        let texture_id = load_definition_texture(textures, path, "texture", &self.texture)?;
+        Ok(Player {
+            name: self.name,
+            pos: Vector2 { x: 50.0, y: 50.0 },
+            prev_pos: Vector2 { x: 50.0, y: 50.0 },
+            vel: Vector2 { x: 1.0, y: 0.0 },
+            dir: Vector2 { x: 0.0, y: 1.0 },
+            prev_dir: Vector2 { x: 0.0, y: 1.0 },
+            speed_original: self.speed,
+            left_turn_original: self.left_turn,
+            right_turn_original: self.right_turn,
+            parts: self.parts.iter().map(PartDefinition::build).collect(),
+            damage: self.damage.iter().map(DamageDefinition::build).collect(),
+            partical_emmiters: self
+                .partical_emitters
+                .iter()
+                .map(ParticalEmitterDefinition::build)
+                .collect(),
+            bullet_emmiters: self
+                .bullet_emitters
+                .iter()
+                .map(|emitter| emitter.build(true))
+                .collect(),
+            speed: 0.0,
+            left_turn: 0.0,
+            right_turn: 0.0,
+            texture_id,
+        })
+    }
+}
+
+impl PartDefinition {
+    fn validate(&self, path: &Path, field: &str) -> Result<(), DataError> {
+        if self.name.is_empty() {
+            return Err(DataError::new(
+                path,
+                format!("{field}.name"),
+                "must not be empty",
+            ));
+        }
+        check_positive(path, format!("{field}.health"), self.health)?;
+        check_positive(path, format!("{field}.size"), self.size)
+    }
+
+    fn build(&self) -> Part {
+        Part {
+            pos: Vector2::zero(),
+            location: Vector2::new(self.location.0, self.location.1),
+            health: self.health,
+            starting_health: self.health,
+            size: self.size,
+            name: self.name.clone(),
+        }
+    }
+}
+
+impl DamageDefinition {
+    fn validate(&self, ship: &ShipDefinition, path: &Path, field: &str) -> Result<(), DataError> {
+        if self.src.is_empty() {
+            return Err(DataError::new(
+                path,
+                format!("{field}.src"),
+                "must reference at least one part",
+            ));
+        }
+        for (index, src) in self.src.iter().enumerate() {
+            if *src >= ship.parts.len() {
+                return Err(DataError::new(
+                    path,
+                    format!("{field}.src[{index}]"),
+                    format!(
+                        "part index {src} is out of range, the ship has {} parts",
+                        ship.parts.len()
+                    ),
+                ));
+            }
+        }
+        let targets = match self.des {
+            PartMod::Partical => Some(("partical emitters", ship.partical_emitters.len())),
+            PartMod::Gun => Some(("bullet emitters", ship.bullet_emitters.len())),
+            PartMod::TurnLeft | PartMod::TurnRight | PartMod::Speed => None,
+        };
+        if let Some((kind, count)) = targets {
+            if self.index >= count {
+                return Err(DataError::new(
+                    path,
+                    format!("{field}.index"),
+                    format!(
+                        "{kind} index {} is out of range, the ship has {count} {kind}",
+                        self.index
+                    ),
+                ));
+            }
+        }
+        check_positive(path, format!("{field}.scale"), self.scale)
+    }
+
+    fn build(&self) -> Damage {
+        Damage {
+            src: self.src.clone(),
+            des: self.des.clone(),
+            index: self.index,
+            damage_type: self.damage_type.clone(),
+            scale: self.scale,
+        }
+    }
+}
+
+impl ParticalEmitterDefinition {
+    fn validate(&self, path: &Path, field: &str) -> Result<(), DataError> {
+        check_not_negative(path, format!("{field}.speed"), self.speed)?;
+        check_positive(path, format!("{field}.size"), self.size)?;
+        check_positive(path, format!("{field}.duration"), self.duration)?;
+        check_not_negative(path, format!("{field}.angular_speed"), self.angular_speed)?;
+        check_positive(
+            path,
+            format!("{field}.particals_per_second"),
+            self.particals_per_second,
+        )
+    }
+
+    fn build(&self) -> ParticalEmitter {
+        ParticalEmitter {
+            pos: Vector2::zero(),
+            location: Vector2::new(self.location.0, self.location.1),
+            vel: Vector2::zero(),
+            speed_orginal: self.speed,
+            size: self.size,
+            shape: self.shape.clone(),
+            starting_color: color(self.starting_color),
+            ending_color: color(self.ending_color),
+            duration: self.duration,
+            partical_interval: 1.0 / self.particals_per_second,
+            time: 0.0,
+            speed: 0.0,
+            angular_speed: self.angular_speed,
+        }
+    }
+}
+
+impl EffectDefinition {
+    fn validate(&self, path: &Path) -> Result<(), DataError> {
+        if self.name.is_empty() {
+            return Err(DataError::new(path, "name", "must not be empty"));
+        }
+        if self.count == 0 {
+            return Err(DataError::new(path, "count", "must be greater than zero"));
+        }
+        check_not_negative(path, "force.0", self.force.0)?;
+        if self.force.1 <= self.force.0 {
+            return Err(DataError::new(
+                path,
+                "force",
+                format!(
+                    "the fastest must be faster than the slowest, got {:?}",
+                    self.force
+                ),
+            ));
+        }
+        check_positive(path, "size", self.size)?;
+        if let Some(ending_size) = self.ending_size {
+            check_not_negative(path, "ending_size", ending_size)?;
+        }
+        check_not_negative(path, "angular_speed", self.angular_speed)?;
+        check_positive(path, "duration", self.duration)
+    }
+
+    fn build(self) -> Effect {
+        Effect {
+            count: self.count,
+            force: self.force,
+            size: self.size,
+            ending_size: self.ending_size.unwrap_or(self.size),
+            shape: self.shape,
+            angular_speed: self.angular_speed,
+            starting_color: color(self.starting_color),
+            ending_color: color(self.ending_color),
+            duration: self.duration,
+        }
+    }
+}
+
+impl BulletEmitterDefinition {
+    fn validate(&self, path: &Path, field: &str) -> Result<(), DataError> {
+        check_positive(path, format!("{field}.size"), self.size)?;
+        check_not_negative(path, format!("{field}.damage"), self.damage)?;
+        check_positive(path, format!("{field}.duration"), self.duration)?;
+        check_positive(
+            path,
+            format!("{field}.bullets_per_second"),
+            self.bullets_per_second,
+        )?;
+        check_not_negative(path, format!("{field}.delay"), self.delay)
+    }
+
+    fn build(&self, friendly: bool) -> BulletEmitter {
+        BulletEmitter {
+            pos: Vector2::zero(),
+            location: Vector2::new(self.location.0, self.location.1),
+            size: self.size,
+            damage: self.damage,
+            friendly,
+            duration: self.duration,
+            bullet_interval_original: 1.0 / self.bullets_per_second,
+            bullet_interval: 1.0 / self.bullets_per_second,
+            time: self.delay,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIP: &str = r#"(
+        name: "Test",
+        texture: "images/V1Ship.png",
+        speed: 250.0,
+        left_turn: 100.0,
+        right_turn: 100.0,
+        parts: [
+            (name: "Body", location: (0.0, 0.0), health: 3.0, size: 20.0),
+            (name: "Engine", location: (0.0, -10.0), health: 2.0, size: 10.0),
+        ],
+        damage: [DAMAGE],
+        bullet_emitters: [
+            (location: (0.0, 10.0), size: 5.0, damage: 2.0, duration: 2.0, bullets_per_second: 5.0),
+        ],
+    )"#;
+
+    /// Loads and checks a ship from `source`, written to a file named after `name`.
+    fn load_ship(name: &str, source: &str) -> Result<ShipDefinition, DataError> {
+        let path = std::env::temp_dir().join(format!(
+            "spaceshooter-data-{}-{name}.ron",
+            std::process::id()
+        ));
+        fs::write(&path, source).unwrap();
+        let result = load_definition::<ShipDefinition>(&path)
+            .and_then(|ship| ship.validate(&path).map(|()| ship));
+        fs::remove_file(&path).unwrap();
+        result
+    }
+
+    fn ship_with_damage(name: &str, damage: &str) -> Result<ShipDefinition, DataError> {
+        load_ship(name, &SHIP.replace("DAMAGE", damage))
+    }
+
+    fn load_error(name: &str, source: &str) -> DataError {
+        load_ship(name, source)
+            .err()
+            .expect("the ship should be rejected")
+    }
+
+    fn damage_error(name: &str, damage: &str) -> DataError {
+        load_error(name, &SHIP.replace("DAMAGE", damage))
+    }
+
+    #[test]
+    fn valid_ship() {
+        let ship = ship_with_damage("valid", "(src: [0, 1], des: Gun, index: 0)").unwrap();
+        assert_eq!(ship.parts.len(), 2);
+    }
+
+    #[test]
+    fn damage_src_out_of_range() {
+        let error = damage_error("src_range", "(src: [0, 2], des: Speed)");
+        assert_eq!(error.field, "damage[0].src[1]");
+    }
+
+    #[test]
+    fn damage_without_src() {
+        let error = damage_error("src_empty", "(src: [], des: Speed)");
+        assert_eq!(error.field, "damage[0].src");
+    }
+
+    #[test]
+    fn damage_index_out_of_range() {
+        let error = damage_error("index_range", "(src: [0], des: Partical, index: 0)");
+        assert_eq!(error.field, "damage[0].index");
+        let error = damage_error("gun_range", "(src: [0], des: Gun, index: 1)");
+        assert_eq!(error.field, "damage[0].index");
+    }
+
+    #[test]
+    fn damage_scale_not_positive() {
+        let error = damage_error(
+            "scale",
+            "(src: [0], des: Speed, damage_type: Div, scale: 0.0)",
+        );
+        assert_eq!(error.field, "damage[0].scale");
+    }
+
+    #[test]
+    fn negative_values() {
+        let source = SHIP
+            .replace("DAMAGE", "")
+            .replace("health: 2.0", "health: -2.0");
+        let error = load_error("health", &source);
+        assert_eq!(error.field, "parts[1].health");
+        let source = SHIP
+            .replace("DAMAGE", "")
+            .replace("bullets_per_second: 5.0", "bullets_per_second: 0.0");
+        let error = load_error("bullets", &source);
+        assert_eq!(error.field, "bullet_emitters[0].bullets_per_second");
+    }
+
+    #[test]
+    fn unknown_field() {
+        let source = SHIP
+            .replace("DAMAGE", "")
+            .replace("size: 20.0", "size: 20.0, armor: 1.0");
+        let error = load_error("unknown", &source);
+        assert_eq!(error.field, "parts[0].armor");
+    }
+
+    #[test]
+    fn syntax_error() {
+        let error = load_error("syntax", "(name: \"Test\"");
+        assert!(error.to_string().contains("spaceshooter-data"), "{error}");
+    }
+
+    #[test]
+    fn missing_file() {
+        let error = load_definition::<ShipDefinition>(Path::new("data/ships/missing.ron"))
+            .err()
+            .unwrap();
+        assert_eq!(error.path, Path::new("data/ships/missing.ron"));
+        assert!(error.field.is_empty());
+    }
+}
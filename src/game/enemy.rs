@@ -1,26 +1,40 @@
 use cgmath::{InnerSpace, MetricSpace, Vector2, Vector4};
 use rand::Rng;
-use slotmap::SlotMap;
 
-use crate::renderer::{
-    texture::{Texture, TextureId},
-    Rendering2D,
-};
+use crate::renderer::{texture::TextureId, Layer, Rendering2D};
 
 use super::{
-    angletovector, enemy_dies, get_2_mut, particalexplosion, rotatevector, vectortoangle, Bullet,
-    Enemy, Partical, Player,
+    angletovector, damage_part, draw_edge_indicator, enemy_grid, interpolate, part_grid,
+    rotatevector, vectortoangle, Bullet, BulletPool, DamageSource, Enemy, EnemyAi, GameEvent,
+    GameRng, KillCause, Partical, Player,
 };
 
+impl Enemy {
+    /// Takes `amount` of health away, blaming `cause` if that destroys the enemy.
+    pub fn damage(&mut self, amount: f32, cause: KillCause) {
+        self.health -= amount;
+        if self.health <= 0.0 {
+            self.killed_by.get_or_insert(cause);
+        }
+    }
+
+    pub fn kill(&mut self, cause: KillCause) {
+        self.health = -1.0;
+        self.killed_by.get_or_insert(cause);
+    }
+}
+
 pub fn update_enemies(
     player: &mut Player,
     enemies: &mut Vec<Enemy>,
     particals: &mut Vec<Partical>,
-    bullets: &mut Vec<Bullet>,
+    bullets: &mut BulletPool,
+    events: &mut Vec<GameEvent>,
+    rng: &mut GameRng,
     dt: f32,
 ) {
-    for enemy_index in 0..enemies.len() {
-        let enemy = &mut enemies[enemy_index];
+    let part_grid = part_grid(player);
+    for enemy in enemies.iter_mut() {
         let right = rotatevector(enemy.dir, std::f32::consts::PI / 2.0);
         let sign = if right.dot(player.pos - enemy.pos) > 0.0 {
             1.0
@@ -34,16 +48,15 @@ pub fn update_enemies(
                 time_to_reach = enemy.targetpos.distance(enemy.pos) / enemy.vel.magnitude();
             }
         } else {
-            if enemy.name == "Basic".to_string() {
-                enemy.targetpos = player.pos
-            }
-
-            if enemy.name == "Turret".to_string() {
-                enemy.targetpos = player.pos
-                    + rotatevector(
-                        (enemy.pos - player.pos).normalize(),
-                        std::f32::consts::TAU / 8.0 * sign,
-                    ) * 200.0
+            match enemy.ai {
+                EnemyAi::Chase => enemy.targetpos = player.pos,
+                EnemyAi::Orbit { angle, distance } => {
+                    enemy.targetpos = player.pos
+                        + rotatevector(
+                            (enemy.pos - player.pos).normalize(),
+                            angle.to_radians() * sign,
+                        ) * distance
+                }
             }
         }
         if right.dot(enemy.targetpos - enemy.pos) > 0.0 {
@@ -71,50 +84,18 @@ pub fn update_enemies(
                 );
             partical_emmiter.vel = enemy.vel
                 + -enemy.dir * partical_emmiter.speed
-                + angletovector(
-                    rand::thread_rng().gen_range(-std::f32::consts::PI..std::f32::consts::PI),
-                ) * rand::thread_rng().gen_range(20.0..40.0);
+                + angletovector(rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI))
+                    * rng.gen_range(20.0..40.0);
             partical_emmiter.time += dt;
             while partical_emmiter.time > partical_emmiter.partical_interval {
-                particals.push(Partical {
-                    pos: partical_emmiter.pos,
-                    vel: partical_emmiter.vel,
-                    size: partical_emmiter.size,
-                    shape: partical_emmiter.shape.clone(),
-                    starting_color: partical_emmiter.starting_color,
-                    ending_color: partical_emmiter.ending_color,
-                    duration: partical_emmiter.duration,
-                    time: 0.0,
-                });
+                particals.push(partical_emmiter.emit(rng));
                 partical_emmiter.time -= partical_emmiter.partical_interval;
             }
         }
-        for part in &mut player.parts {
-            if enemy.pos.distance(part.pos) < part.size + enemy.size {
-                enemy.health = -1.0;
-                part.health -= 1.0;
-                particalexplosion(
-                    particals,
-                    part.pos,
-                    player.vel,
-                    0.0,
-                    300.0,
-                    500,
-                    Vector4 {
-                        x: 140.0 / 255.0,
-                        y: 255.0 / 255.0,
-                        z: 251.0 / 255.0,
-                        w: 255.0 / 255.0,
-                    },
-                    Vector4 {
-                        x: 255.0 / 255.0,
-                        y: 0.0 / 255.0,
-                        z: 50.0 / 255.0,
-                        w: 0.0 / 255.0,
-                    },
-                    1.0,
-                );
-            }
+        for part_index in part_grid.overlapping(enemy.pos, enemy.size) {
+            enemy.kill(KillCause::Rammed);
+            let pos = player.parts[part_index].pos;
+            damage_part(player, part_index, 1.0, DamageSource::Rammed, pos, events);
         }
         for bullet_emmiter in &mut enemy.bullet_emmiters {
             bullet_emmiter.pos = enemy.pos
@@ -127,41 +108,60 @@ pub fn update_enemies(
             bullet_emmiter.time += dt;
             while bullet_emmiter.time > bullet_emmiter.bullet_interval {
                 if true {
-                    bullets.push(Bullet {
+                    bullets.fire(Bullet {
                         pos: bullet_emmiter.pos,
-                        vel: vel,
+                        prev_pos: bullet_emmiter.pos,
+                        vel,
                         size: bullet_emmiter.size,
                         damage: bullet_emmiter.damage,
                         friendly: bullet_emmiter.friendly,
                         duration: bullet_emmiter.duration,
                         time: 0.0,
                     });
+                    events.push(GameEvent::BulletFired {
+                        pos: bullet_emmiter.pos,
+                        vel,
+                        friendly: bullet_emmiter.friendly,
+                    });
                 }
                 bullet_emmiter.time -= bullet_emmiter.bullet_interval;
             }
         }
-        bullets.retain(|bullet| {
-            bullet.pos.distance(enemy.pos) > bullet.size * 2.0 + enemy.size || !bullet.friendly
-        });
-        for part in &player.parts {
-            bullets.retain(|bullet| {
-                bullet.pos.distance(part.pos) > bullet.size * 2.0 + part.size || bullet.friendly
-            });
-        }
-        for other_enemy_index in 0..enemies.len() {
-            let Some((enemy, other_enemy)) = get_2_mut(enemies, enemy_index, other_enemy_index)
-            else {
-                continue;
-            };
-            if enemy.pos.distance(other_enemy.pos) < other_enemy.size + enemy.size {
-                enemy.health = -1.0;
-                other_enemy.health = -1.0;
-            };
+    }
+
+    let enemy_grid = enemy_grid(enemies);
+    bullets.retain(|bullet| {
+        let grid = if bullet.friendly {
+            &enemy_grid
+        } else {
+            &part_grid
+        };
+        grid.overlapping(bullet.pos, bullet.size * 2.0)
+            .next()
+            .is_none()
+    });
+    // Enemies collide with each other once all of them have moved, so the order they are
+    // updated in makes no difference. Checking as each one moved used to let an enemy run into
+    // where another one had been a tick earlier.
+    for enemy_index in 0..enemies.len() {
+        let (pos, size) = (enemies[enemy_index].pos, enemies[enemy_index].size);
+        for other_enemy_index in enemy_grid.overlapping(pos, size) {
+            if other_enemy_index != enemy_index {
+                enemies[enemy_index].kill(KillCause::Collision);
+                enemies[other_enemy_index].kill(KillCause::Collision);
+            }
         }
     }
-    for enemy in enemies.iter_mut() {
-        if enemy.health <= 0.0 {
-            enemy_dies(enemy.pos, enemy.vel, particals);
+    for enemy in enemies.iter() {
+        // Health only ever reaches zero through `damage` or `kill`, which record the cause.
+        if let (true, Some(cause)) = (enemy.health <= 0.0, enemy.killed_by) {
+            events.push(GameEvent::EnemyKilled {
+                enemy: enemy.name.clone(),
+                pos: enemy.pos,
+                vel: enemy.vel,
+                points: enemy.points,
+                cause,
+            });
         }
     }
     enemies.retain(|enemy| enemy.health > 0.0);
@@ -172,11 +172,13 @@ pub fn draw_enemies(
     player: &Player,
     enemies: &Vec<Enemy>,
     enemy_warning_image: &TextureId,
+    alpha: f32,
 ) {
+    let player_pos = interpolate(player.prev_pos, player.pos, alpha);
     for enemy in enemies {
-
+        let enemy_pos = interpolate(enemy.prev_pos, enemy.pos, alpha);
         drawing.draw_quad(
-            enemy.pos,
+            enemy_pos,
             Vector2 { x: 32.0, y: 32.0 },
             Vector4 {
                 x: 1.0,
@@ -184,12 +186,13 @@ pub fn draw_enemies(
                 z: 1.0,
                 w: 1.0,
             },
-            vectortoangle(enemy.dir).to_degrees() - 90.0,
+            vectortoangle(interpolate(enemy.prev_dir, enemy.dir, alpha)).to_degrees() - 90.0,
             Some(enemy.texture_id),
+            Layer::Ships,
         );
-        if enemy.name == "Turret" {
+        if let Some(turret_texture_id) = enemy.turret_texture_id {
             drawing.draw_quad(
-                enemy.pos,
+                enemy_pos,
                 Vector2 { x: 32.0, y: 32.0 },
                 Vector4 {
                     x: 1.0,
@@ -197,23 +200,76 @@ pub fn draw_enemies(
                     z: 1.0,
                     w: 1.0,
                 },
-                (player.pos - enemy.pos).normalize().angle(Vector2::unit_y()).0.to_degrees() - 90.0,
-                Some(enemy.extra_texture_ids[0]),
-            );
-        }
-        if player.pos.distance(enemy.pos) > 170.0 {
-            drawing.draw_quad(
-                (enemy.pos - player.pos).normalize() * 170.0 + player.pos,
-                Vector2 { x: 32.0, y: 32.0 },
-                Vector4 {
-                    x: 1.0,
-                    y: 1.0,
-                    z: 1.0,
-                    w: 1.0,
-                },
-                0.0,
-                Some(*enemy_warning_image),
+                (player_pos - enemy_pos)
+                    .normalize()
+                    .angle(Vector2::unit_y())
+                    .0
+                    .to_degrees()
+                    - 90.0,
+                Some(turret_texture_id),
+                Layer::Ships,
             );
         }
+        draw_edge_indicator(drawing, enemy_pos, *enemy_warning_image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::game::{find_enemy, test_assets};
+
+    /// Runs one tick for two basic enemies far from the player, flying along x.
+    fn collide(first: (f32, f32), second: (f32, f32)) -> Vec<GameEvent> {
+        let assets = test_assets();
+        let mut player = assets.ships[0].clone();
+        let enemy = |(x, vel): (f32, f32)| {
+            let mut enemy = find_enemy(&assets.enemies, "Basic").unwrap().clone();
+            enemy.pos = Vector2::new(x, 10000.0);
+            enemy.vel = Vector2::new(vel, 0.0);
+            enemy.dir = Vector2::new(vel.signum(), 0.0);
+            enemy
+        };
+        let mut enemies = vec![enemy(first), enemy(second)];
+        let mut events = vec![];
+        update_enemies(
+            &mut player,
+            &mut enemies,
+            &mut vec![],
+            &mut BulletPool::default(),
+            &mut events,
+            &mut GameRng::seed_from_u64(0),
+            1.0 / 60.0,
+        );
+        events
+    }
+
+    fn collisions(events: &[GameEvent]) -> usize {
+        events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    GameEvent::EnemyKilled {
+                        cause: KillCause::Collision,
+                        ..
+                    }
+                )
+            })
+            .count()
+    }
+
+    #[test]
+    fn enemies_in_formation_do_not_collide() {
+        // Just out of reach of each other, with the one behind updated first.
+        assert_eq!(collisions(&collide((0.0, 600.0), (33.0, 600.0))), 0);
+    }
+
+    #[test]
+    fn enemies_collide_where_they_end_up() {
+        // Out of reach before the tick, overlapping after it.
+        assert_eq!(collisions(&collide((0.0, 600.0), (45.0, -600.0))), 2);
     }
 }
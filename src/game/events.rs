@@ -0,0 +1,68 @@
+use cgmath::Vector2;
+
+use super::{KillCause, PowerUpType};
+
+/// Something that happened during a tick. The simulation only reports these, and the score,
+/// effects and anything outside the game react to them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    EnemyKilled {
+        enemy: String,
+        pos: Vector2<f32>,
+        vel: Vector2<f32>,
+        points: u32,
+        cause: KillCause,
+    },
+    /// A player bullet hit an enemy, whether or not it killed it.
+    EnemyHit {
+        pos: Vector2<f32>,
+    },
+    PartDamaged {
+        part: usize,
+        /// Where the part was hit.
+        pos: Vector2<f32>,
+        amount: f32,
+        source: DamageSource,
+    },
+    /// A part's health reached zero.
+    PartDestroyed {
+        part: usize,
+        pos: Vector2<f32>,
+    },
+    PowerUpCollected {
+        kind: PowerUpType,
+        part: usize,
+        pos: Vector2<f32>,
+    },
+    BulletFired {
+        pos: Vector2<f32>,
+        vel: Vector2<f32>,
+        friendly: bool,
+    },
+    WaveSpawned {
+        enemy: String,
+        count: u32,
+    },
+}
+
+/// What hurt one of the player's parts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageSource {
+    Bullet,
+    /// An enemy flew into it.
+    Rammed,
+}
+
+impl GameEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::EnemyKilled { .. } => "EnemyKilled",
+            GameEvent::EnemyHit { .. } => "EnemyHit",
+            GameEvent::PartDamaged { .. } => "PartDamaged",
+            GameEvent::PartDestroyed { .. } => "PartDestroyed",
+            GameEvent::PowerUpCollected { .. } => "PowerUpCollected",
+            GameEvent::BulletFired { .. } => "BulletFired",
+            GameEvent::WaveSpawned { .. } => "WaveSpawned",
+        }
+    }
+}
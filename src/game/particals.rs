@@ -1,55 +1,143 @@
 use cgmath::Vector2;
+use rand::Rng;
 
-use crate::renderer::Rendering2D;
+use crate::renderer::{particals::ParticalSpawn, Layer, Rendering2D};
 
-use super::{colorlerp, Partical, ParticalShape, Player};
+use super::{
+    angletovector, colorlerp, interpolate, Effects, GameRng, Partical, ParticalEmitter,
+    ParticalShape,
+};
+
+impl ParticalEmitter {
+    /// A new partical where the emitter is, moving the way it is.
+    pub fn emit(&self, rng: &mut GameRng) -> Partical {
+        // Only spinning particals use the rng, so other emitters play out the same as before.
+        let (rotation, angular_vel) = match self.shape {
+            ParticalShape::RotSquare => (
+                rng.gen_range(0.0..90.0),
+                rng.gen_range(-1.0..=1.0) * self.angular_speed,
+            ),
+            ParticalShape::Square | ParticalShape::Circle => (0.0, 0.0),
+        };
+        Partical {
+            pos: self.pos,
+            prev_pos: self.pos,
+            vel: self.vel,
+            size: self.size,
+            ending_size: self.size,
+            shape: self.shape.clone(),
+            rotation,
+            prev_rotation: rotation,
+            angular_vel,
+            starting_color: self.starting_color,
+            ending_color: self.ending_color,
+            duration: self.duration,
+            time: 0.0,
+        }
+    }
+}
+
+impl Partical {
+    /// This partical as it is now, for the GPU to carry on with.
+    pub fn spawn(&self) -> ParticalSpawn {
+        ParticalSpawn {
+            pos: self.pos,
+            vel: self.vel,
+            size: self.size,
+            ending_size: self.ending_size,
+            circle: matches!(self.shape, ParticalShape::Circle),
+            rotation: self.rotation,
+            angular_vel: self.angular_vel,
+            starting_color: self.starting_color,
+            ending_color: self.ending_color,
+            duration: self.duration,
+            time: self.time,
+        }
+    }
+}
+
+impl Effects {
+    /// Plays the effect called `name` at `pos`, with every partical also moving at `vel`.
+    pub fn play(
+        &self,
+        name: &str,
+        particals: &mut Vec<Partical>,
+        rng: &mut GameRng,
+        pos: Vector2<f32>,
+        vel: Vector2<f32>,
+    ) {
+        let Some(effect) = self.effects.get(name) else {
+            return;
+        };
+        for _ in 0..effect.count {
+            let vel = vel
+                + angletovector(rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI))
+                    * rng.gen_range(effect.force.0..effect.force.1);
+            let (rotation, angular_vel) = match effect.shape {
+                ParticalShape::RotSquare => (
+                    rng.gen_range(0.0..90.0),
+                    rng.gen_range(-1.0..=1.0) * effect.angular_speed,
+                ),
+                ParticalShape::Square | ParticalShape::Circle => (0.0, 0.0),
+            };
+            particals.push(Partical {
+                pos,
+                prev_pos: pos,
+                vel,
+                size: effect.size,
+                ending_size: effect.ending_size,
+                shape: effect.shape.clone(),
+                rotation,
+                prev_rotation: rotation,
+                angular_vel,
+                starting_color: effect.starting_color,
+                ending_color: effect.ending_color,
+                duration: effect.duration,
+                time: 0.0,
+            });
+        }
+    }
+}
 
 pub fn update_particals(particals: &mut Vec<Partical>, dt: f32) {
     for partical in &mut *particals {
         partical.pos += partical.vel * dt;
+        partical.rotation += partical.angular_vel * dt;
         partical.time += dt;
     }
     particals.retain(|partical| partical.time < partical.duration);
 }
 
-pub fn draw_particals(
-    drawing: &mut Rendering2D<'_, '_>,
-    particals: &mut Vec<Partical>,
-) {
+pub fn draw_particals(drawing: &mut Rendering2D<'_, '_>, particals: &[Partical], alpha: f32) {
     for partical in particals {
-        let lerped_color = colorlerp(
-            partical.starting_color,
-            partical.ending_color,
-            partical.time / partical.duration,
-        );
+        let t = partical.time / partical.duration;
+        let lerped_color = colorlerp(partical.starting_color, partical.ending_color, t);
+        let size = partical.size + (partical.ending_size - partical.size) * t;
+        let pos = interpolate(partical.prev_pos, partical.pos, alpha);
         match partical.shape {
             ParticalShape::Square => {
                 drawing.draw_quad(
-                    partical.pos,
-                    Vector2::new(partical.size, partical.size),
+                    pos,
+                    Vector2::new(size, size),
                     lerped_color,
                     0.0,
                     None,
+                    Layer::Particals,
+                );
+            }
+            ParticalShape::Circle => {
+                drawing.draw_circle(pos, size / 2.0, lerped_color, Layer::Particals);
+            }
+            ParticalShape::RotSquare => {
+                drawing.draw_quad(
+                    pos,
+                    Vector2::new(size, size),
+                    lerped_color,
+                    partical.prev_rotation + (partical.rotation - partical.prev_rotation) * alpha,
+                    None,
+                    Layer::Particals,
                 );
             }
-            //    drawing.draw_rectangle_v(
-            //    Vector2::new(
-            //        partical.pos.x - player.pos.x + screenwidth as f32 / 2.0 - partical.size / 2.0,
-            //        partical.pos.y - player.pos.y + screenheight as f32 / 2.0 - partical.size / 2.0,
-            //    ),
-            //    Vector2::new(partical.size, partical.size),
-            //    lerped_color,
-            //),
-            ParticalShape::Circle => {}
-            //    drawing.draw_circle_v(
-            //    Vector2::new(
-            //        partical.pos.x - player.pos.x + screenwidth as f32 / 2.0,
-            //        partical.pos.y - player.pos.y + screenheight as f32 / 2.0,
-            //    ),
-            //    partical.size / 2.0,
-            //    lerped_color,
-            //),
-            ParticalShape::RotSquare => {}
         }
     }
 }
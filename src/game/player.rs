@@ -1,170 +1,28 @@
-use std::path::Path;
-
-use crate::renderer::{self, texture::TextureId, Renderer, Rendering2D};
+use crate::{
+    input::PlayerInput,
+    renderer::{texture::TextureId, Layer, Rendering2D},
+};
 
 use super::{
-    angletovector, load_texture, rotatevector, vectortoangle, Bullet, BulletEmitter, Damage, DamageType, Enemy, Part, PartMod, Partical, ParticalEmitter, ParticalShape, Player
+    angletovector, interpolate, rotatevector, vectortoangle, Bullet, BulletPool, DamageSource,
+    DamageType, Enemy, GameEvent, GameRng, PartMod, Partical, Player,
 };
-use cgmath::{InnerSpace, Vector2, Vector4, Zero};
+use cgmath::{InnerSpace, Vector2, Vector4};
 use rand::Rng;
-use inputbot::KeybdKey::*;
 
-pub fn init_player(renderer:&mut Renderer) -> Player {
-    Player {
-        pos: Vector2 { x: 50.0, y: 50.0 },
-        vel: Vector2 { x: 1.0, y: 0.0 },
-        dir: Vector2 { x: 0.0, y: 1.0 },
-        speed_original: 250.0,
-        left_turn_original: 100.0,
-        right_turn_original: 100.0,
-        parts: vec![
-            Part {
-                pos: Vector2::zero(),
-                location: Vector2 { x: 12.0, y: -13.0 },
-                health: 4.0,
-                starting_health: 4.0,
-                size: 17.0,
-                name: "Left Engine".to_string(),
-            },
-            Part {
-                pos: Vector2::zero(),
-                location: Vector2 { x: -12.0, y: -13.0 },
-                health: 4.0,
-                starting_health: 4.0,
-                size: 17.0,
-                name: "Right Engine".to_string(),
-            },
-            Part {
-                pos: Vector2::zero(),
-                location: Vector2 { x: 0.0, y: 15.0 },
-                health: 3.0,
-                starting_health: 3.0,
-                size: 20.0,
-                name: "Main Body".to_string(),
-            },
-        ],
-        damage: vec![
-            Damage {
-                src: vec![1],
-                des: PartMod::TurnLeft,
-                index: 0,
-                damage_type: DamageType::Mult,
-                scale: 1.0,
-            },
-            Damage {
-                src: vec![0],
-                des: PartMod::TurnRight,
-                index: 0,
-                damage_type: DamageType::Mult,
-                scale: 1.0,
-            },
-            Damage {
-                src: vec![0, 1, 2],
-                des: PartMod::Speed,
-                index: 0,
-                damage_type: DamageType::Mult,
-                scale: 1.0,
-            },
-            Damage {
-                src: vec![0],
-                des: PartMod::Partical,
-                index: 0,
-                damage_type: DamageType::Mult,
-                scale: 1.0,
-            },
-            Damage {
-                src: vec![1],
-                des: PartMod::Partical,
-                index: 1,
-                damage_type: DamageType::Mult,
-                scale: 1.0,
-            },
-        ],
-        partical_emmiters: vec![
-            ParticalEmitter {
-                pos: Vector2::zero(),
-                location: Vector2::new(21.0, -26.0),
-                vel: Vector2::zero(),
-                speed_orginal: 200.0,
-                size: 5.0,
-                shape: ParticalShape::Square,
-                starting_color: Vector4 {
-                    x: 140.0 / 255.0,
-                    y: 255.0 / 255.0,
-                    z: 251.0 / 255.0,
-                    w: 255.0 / 255.0,
-                },
-                ending_color: Vector4 {
-                    x: 255.0 / 255.0,
-                    y: 0.0 / 255.0,
-                    z: 50.0 / 255.0,
-                    w: 0.0 / 255.0,
-                },
-                duration: 1.0,
-                partical_interval: 1.0 / 400.0,
-                time: 0.0,
-                speed: 0.0,
-            },
-            ParticalEmitter {
-                pos: Vector2::zero(),
-                location: Vector2::new(-21.0, -26.0),
-                vel: Vector2::zero(),
-                speed_orginal: 200.0,
-                size: 5.0,
-                shape: ParticalShape::Square,
-                starting_color: Vector4 {
-                    x: 140.0 / 255.0,
-                    y: 255.0 / 255.0,
-                    z: 251.0 / 255.0,
-                    w: 255.0 / 255.0,
-                },
-                ending_color: Vector4 {
-                    x: 255.0 / 255.0,
-                    y: 0.0 / 255.0,
-                    z: 50.0 / 255.0,
-                    w: 0.0 / 255.0,
-                },
-                duration: 1.0,
-                partical_interval: 1.0 / 400.0,
-                time: 0.0,
-                speed: 0.0,
-            },
-        ],
-        bullet_emmiters: vec![
-            BulletEmitter {
-                pos: Vector2::zero(),
-                location: Vector2 { x: 17.0, y: 13.0 },
-                size: 5.0,
-                damage: 2.0,
-                friendly: true,
-                duration: 2.0,
-                bullet_interval: 1.0 / 7.5,
-                time: 0.0,
-            },
-            BulletEmitter {
-                pos: Vector2::zero(),
-                location: Vector2 { x: -17.0, y: 13.0 },
-                size: 5.0,
-                damage: 2.0,
-                friendly: true,
-                duration: 2.0,
-                bullet_interval: 1.0 / 5.0,
-                time: 1.0 / 7.5 / 2.0,
-            },
-        ],
-        speed: 0.0,
-        left_turn: 0.0,
-        right_turn: 0.0,
-        texture_id: load_texture(renderer, "Player Texture", Path::new("images/V1Ship.png")),
-    }
-}
+/// The least a damage route scales a value by, so destroyed parts never zero out or divide
+/// by zero.
+const MIN_DAMAGE_FACTOR: f32 = 0.05;
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_player(
     player: &mut Player,
+    input: PlayerInput,
     enemies: &mut Vec<Enemy>,
-    bullets: &mut Vec<Bullet>,
+    bullets: &mut BulletPool,
     particals: &mut Vec<Partical>,
-   // rl: &RaylibHandle,
+    events: &mut Vec<GameEvent>,
+    rng: &mut GameRng,
     dt: f32,
 ) {
     player.left_turn = player.left_turn_original;
@@ -174,8 +32,11 @@ pub fn update_player(
     for partical_emmiter in &mut player.partical_emmiters {
         partical_emmiter.speed = partical_emmiter.speed_orginal
     }
+    for bullet_emmiter in &mut player.bullet_emmiters {
+        bullet_emmiter.bullet_interval = bullet_emmiter.bullet_interval_original
+    }
 
-    let mut fire: bool = false;
+    let mut fire: bool = input.fire;
     for enemy in enemies {
         for partical_emmiter in &mut enemy.partical_emmiters {
             partical_emmiter.speed = partical_emmiter.speed_orginal
@@ -192,40 +53,38 @@ pub fn update_player(
             health += player.parts[*src].health;
             total_health += player.parts[*src].starting_health;
         }
-        let value = health / total_health;
+        let factor = (health / total_health * damage.scale).max(MIN_DAMAGE_FACTOR);
         match damage.damage_type {
             DamageType::Mult => match damage.des {
-                PartMod::Partical => {
-                    player.partical_emmiters[damage.index].speed *= value * damage.scale
-                }
-                PartMod::Gun => {
-                    player.bullet_emmiters[damage.index].bullet_interval *= value * damage.scale
-                }
-                PartMod::TurnLeft => player.left_turn *= value * damage.scale,
-                PartMod::TurnRight => player.right_turn *= value * damage.scale,
-                PartMod::Speed => player.speed *= value * damage.scale,
+                PartMod::Partical => player.partical_emmiters[damage.index].speed *= factor,
+                PartMod::Gun => player.bullet_emmiters[damage.index].bullet_interval *= factor,
+                PartMod::TurnLeft => player.left_turn *= factor,
+                PartMod::TurnRight => player.right_turn *= factor,
+                PartMod::Speed => player.speed *= factor,
             },
             DamageType::Div => match damage.des {
-                PartMod::Partical => {
-                    player.partical_emmiters[damage.index].speed /= value * damage.scale
-                }
-                PartMod::Gun => {
-                    player.bullet_emmiters[damage.index].bullet_interval /= value * damage.scale
-                }
-                PartMod::TurnLeft => player.left_turn /= value * damage.scale,
-                PartMod::TurnRight => player.right_turn /= value * damage.scale,
-                PartMod::Speed => player.speed /= value * damage.scale,
+                PartMod::Partical => player.partical_emmiters[damage.index].speed /= factor,
+                PartMod::Gun => player.bullet_emmiters[damage.index].bullet_interval /= factor,
+                PartMod::TurnLeft => player.left_turn /= factor,
+                PartMod::TurnRight => player.right_turn /= factor,
+                PartMod::Speed => player.speed /= factor,
             },
         }
     }
-    if AKey.is_pressed() {
-        player.dir =
-            angletovector(vectortoangle(player.dir) + (player.left_turn.to_radians() * dt));
-    };
-    if DKey.is_pressed() {
-           player.dir =
-               angletovector(vectortoangle(player.dir) - (player.right_turn.to_radians() * dt));
-    };
+    player.speed *= input.thrust;
+    for partical_emmiter in &mut player.partical_emmiters {
+        partical_emmiter.speed *= input.thrust;
+    }
+    // Damage only ever slows a gun down.
+    for bullet_emmiter in &mut player.bullet_emmiters {
+        bullet_emmiter.bullet_interval = bullet_emmiter
+            .bullet_interval
+            .max(bullet_emmiter.bullet_interval_original);
+    }
+    let turn = player.left_turn * input.turn_left - player.right_turn * input.turn_right;
+    if turn != 0.0 {
+        player.dir = angletovector(vectortoangle(player.dir) + (turn.to_radians() * dt));
+    }
     player.vel += player.dir.normalize()
         * (player.speed
             - (player.vel.magnitude() * (2.0 + (player.vel.normalize().dot(player.dir) - 1.0))
@@ -248,21 +107,11 @@ pub fn update_player(
                 vectortoangle(player.dir) - std::f32::consts::PI / 2.0,
             );
         partical_emmiter.vel = player.vel
-            + -player.dir * partical_emmiter.speed * (player.parts[1].health / 2.0)
-            + angletovector(
-                rand::thread_rng().gen_range(-std::f32::consts::PI..std::f32::consts::PI),
-            ) * rand::thread_rng().gen_range(20.0..40.0);
+            + -player.dir * partical_emmiter.speed
+            + angletovector(rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI))
+                * rng.gen_range(20.0..40.0);
         while partical_emmiter.time > partical_emmiter.partical_interval {
-            particals.push(Partical {
-                pos: partical_emmiter.pos,
-                vel: partical_emmiter.vel,
-                size: partical_emmiter.size,
-                shape: partical_emmiter.shape.clone(),
-                starting_color: partical_emmiter.starting_color,
-                ending_color: partical_emmiter.ending_color,
-                duration: partical_emmiter.duration,
-                time: 0.0,
-            });
+            particals.push(partical_emmiter.emit(rng));
             partical_emmiter.time -= partical_emmiter.partical_interval;
         }
         partical_emmiter.time += dt;
@@ -277,15 +126,21 @@ pub fn update_player(
         let vel = player.vel + player.dir * 500.0;
         while bullet_emmiter.time > bullet_emmiter.bullet_interval {
             if fire {
-                bullets.push(Bullet {
+                bullets.fire(Bullet {
                     pos: bullet_emmiter.pos,
-                    vel: vel,
+                    prev_pos: bullet_emmiter.pos,
+                    vel,
                     size: bullet_emmiter.size,
                     damage: bullet_emmiter.damage,
                     friendly: bullet_emmiter.friendly,
                     duration: bullet_emmiter.duration,
                     time: 0.0,
                 });
+                events.push(GameEvent::BulletFired {
+                    pos: bullet_emmiter.pos,
+                    vel,
+                    friendly: bullet_emmiter.friendly,
+                });
             }
             bullet_emmiter.time -= bullet_emmiter.bullet_interval;
         }
@@ -293,19 +148,55 @@ pub fn update_player(
     }
 }
 
-pub fn draw_player(drawing: &mut Rendering2D<'_, '_>, player: &Player, ship_image: TextureId) {
+/// Takes `amount` of health away from a part that was hit at `pos`, reporting it and whether
+/// that destroyed the part.
+pub fn damage_part(
+    player: &mut Player,
+    part_index: usize,
+    amount: f32,
+    source: DamageSource,
+    pos: Vector2<f32>,
+    events: &mut Vec<GameEvent>,
+) {
+    let part = &mut player.parts[part_index];
+    let was_intact = part.health > 0.0;
+    part.health -= amount;
+    events.push(GameEvent::PartDamaged {
+        part: part_index,
+        pos,
+        amount,
+        source,
+    });
+    if was_intact && part.health <= 0.0 {
+        events.push(GameEvent::PartDestroyed {
+            part: part_index,
+            pos: part.pos,
+        });
+    }
+}
+
+pub fn draw_player(
+    drawing: &mut Rendering2D<'_, '_>,
+    player: &Player,
+    ship_image: TextureId,
+    alpha: f32,
+) {
     let ship_scale = 1.0;
     drawing.draw_quad(
-        player.pos,
-        Vector2 { x: 64.0 * ship_scale, y: 64.0 * ship_scale},
+        interpolate(player.prev_pos, player.pos, alpha),
+        Vector2 {
+            x: 64.0 * ship_scale,
+            y: 64.0 * ship_scale,
+        },
         Vector4 {
             x: 1.0,
             y: 1.0,
             z: 1.0,
             w: 1.0,
         },
-        vectortoangle(player.dir).to_degrees() - 90.0,
+        vectortoangle(interpolate(player.prev_dir, player.dir, alpha)).to_degrees() - 90.0,
         Some(ship_image),
+        Layer::Ships,
     );
     //drawing.draw_texture_pro(
     //    &ship_image,
@@ -324,3 +215,113 @@ pub fn draw_player(drawing: &mut Rendering2D<'_, '_>, player: &Player, ship_imag
     //    Color::WHITE,
     //);
 }
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Zero;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::game::{test_assets, Damage};
+
+    /// Flies the first ship for ten seconds with every damage route on a destroyed part.
+    fn fly_destroyed(damage_type: DamageType) -> (Player, BulletPool, Vec<Partical>) {
+        let mut player = test_assets().ships[0].clone();
+        player.damage = [
+            PartMod::Partical,
+            PartMod::Gun,
+            PartMod::TurnLeft,
+            PartMod::TurnRight,
+            PartMod::Speed,
+        ]
+        .into_iter()
+        .map(|des| Damage {
+            src: vec![0],
+            des,
+            index: 0,
+            damage_type: damage_type.clone(),
+            scale: 1.0,
+        })
+        .collect();
+        player.parts[0].health = -3.0;
+
+        let mut bullets = BulletPool::default();
+        let mut particals = vec![];
+        let mut rng = GameRng::seed_from_u64(0);
+        for _ in 0..600 {
+            update_player(
+                &mut player,
+                PlayerInput {
+                    turn_left: 1.0,
+                    turn_right: 0.0,
+                    thrust: 1.0,
+                    fire: true,
+                },
+                &mut vec![],
+                &mut bullets,
+                &mut particals,
+                &mut vec![],
+                &mut rng,
+                1.0 / 60.0,
+            );
+        }
+        (player, bullets, particals)
+    }
+
+    fn is_finite(vector: Vector2<f32>) -> bool {
+        vector.x.is_finite() && vector.y.is_finite()
+    }
+
+    #[test]
+    fn destroyed_parts_weaken_without_breaking_the_ship() {
+        for damage_type in [DamageType::Mult, DamageType::Div] {
+            let (player, bullets, particals) = fly_destroyed(damage_type);
+            assert!(is_finite(player.pos) && is_finite(player.vel) && is_finite(player.dir));
+            for value in [player.speed, player.left_turn, player.right_turn] {
+                assert!(value.is_finite() && value > 0.0, "{value}");
+            }
+            assert!(particals
+                .iter()
+                .all(|partical| is_finite(partical.pos) && is_finite(partical.vel)));
+
+            let gun = &player.bullet_emmiters[0];
+            assert!(gun.bullet_interval >= gun.bullet_interval_original);
+            // Ten seconds of both guns at full rate, and no more.
+            let full_rate: f32 = player
+                .bullet_emmiters
+                .iter()
+                .map(|gun| 10.0 / gun.bullet_interval_original)
+                .sum();
+            assert!(bullets.stats().fired as f32 <= full_rate.ceil() + 2.0);
+            assert!(!player.vel.is_zero());
+        }
+    }
+
+    #[test]
+    fn braking_slows_the_ship() {
+        let mut player = test_assets().ships[0].clone();
+        let mut rng = GameRng::seed_from_u64(0);
+        let mut fly = |player: &mut Player, thrust: f32| {
+            for _ in 0..180 {
+                update_player(
+                    player,
+                    PlayerInput {
+                        thrust,
+                        ..PlayerInput::default()
+                    },
+                    &mut vec![],
+                    &mut BulletPool::default(),
+                    &mut vec![],
+                    &mut vec![],
+                    &mut rng,
+                    1.0 / 60.0,
+                );
+            }
+            player.vel.magnitude()
+        };
+        let cruising = fly(&mut player, 1.0);
+        let braked = fly(&mut player, 0.0);
+        assert!(braked < cruising / 2.0, "{braked} {cruising}");
+        assert_eq!(player.speed, 0.0);
+    }
+}
@@ -1,48 +1,49 @@
-use super::{angletovector, get_2_mut, Player, PowerUp, PowerUpType};
-use crate::renderer::Rendering2D;
-use cgmath::{InnerSpace, MetricSpace, Vector2, Vector4};
+use super::{
+    angletovector, draw_edge_indicator, get_2_mut, part_grid, GameEvent, GameRng, Player, PowerUp,
+    PowerUpType,
+};
+use crate::renderer::{Layer, Rendering2D};
+use cgmath::{Vector2, Vector4};
 use rand::prelude::*;
 
-pub fn power_ups_update(
-    drawing: &mut Rendering2D<'_, '_>,
+pub fn update_power_ups(
     player: &mut Player,
     power_ups: &mut Vec<PowerUp>,
+    events: &mut Vec<GameEvent>,
+    rng: &mut GameRng,
 ) {
+    let part_grid = part_grid(player);
     for power_up in power_ups {
-        for part_index in 0..player.parts.len() {
-            let part = &mut player.parts[part_index];
-            if part.pos.distance(power_up.pos) < part.size + 16.0 {
-                match power_up.power_type {
-                    PowerUpType::Shield => {}
-                    PowerUpType::Repair => {
-                        for other_part_index in 0..player.parts.len() {
-                            let Some((part, other_part)) =
-                                get_2_mut(&mut player.parts, part_index, other_part_index)
-                            else {
-                                continue;
-                            };
-                            part.health = part.starting_health;
-                            other_part.health = other_part.starting_health;
-                        }
-                        power_up.pos = player.pos
-                            + angletovector(
-                                rand::thread_rng()
-                                    .gen_range(-std::f32::consts::PI..std::f32::consts::PI),
-                            ) * rand::thread_rng().gen_range(2000.0..2500.0)
+        let touching = part_grid.overlapping(power_up.pos, 16.0).next();
+        if let Some(part_index) = touching {
+            events.push(GameEvent::PowerUpCollected {
+                kind: power_up.power_type,
+                part: part_index,
+                pos: power_up.pos,
+            });
+            match power_up.power_type {
+                PowerUpType::Shield => {}
+                PowerUpType::Repair => {
+                    for other_part_index in 0..player.parts.len() {
+                        let Some((part, other_part)) =
+                            get_2_mut(&mut player.parts, part_index, other_part_index)
+                        else {
+                            continue;
+                        };
+                        part.health = part.starting_health;
+                        other_part.health = other_part.starting_health;
                     }
+                    power_up.pos = player.pos
+                        + angletovector(rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI))
+                            * rng.gen_range(2000.0..2500.0)
                 }
             }
         }
-        //d.draw_texture_v(
-        //    &power_up.texture,
-        //    power_up.pos - player.pos
-        //        + Vector2::new(screenwidth as f32 / 2.0, screenheight as f32 / 2.0)
-        //        - Vector2::new(
-        //            *&power_up.texture.width as f32 / 2.0,
-        //            *&power_up.texture.height as f32 / 2.0,
-        //        ),
-        //    Color::WHITE,
-        //);
+    }
+}
+
+pub fn draw_power_ups(drawing: &mut Rendering2D<'_, '_>, power_ups: &[PowerUp]) {
+    for power_up in power_ups {
         drawing.draw_quad(
             power_up.pos,
             Vector2 { x: 32.0, y: 32.0 },
@@ -54,30 +55,8 @@ pub fn power_ups_update(
             },
             0.0,
             Some(power_up.texture),
+            Layer::Ships,
         );
-        if player.pos.distance(power_up.pos) > 210.0 {
-            //d.draw_texture_v(
-            //    &&power_up.texture,
-            //    (power_up.pos - player.pos).normalized() * 210.0
-            //        + Vector2::new(screenwidth as f32 / 2.0, screenheight as f32 / 2.0)
-            //        - Vector2::new(
-            //            *&power_up.texture.width as f32 / 2.0,
-            //            *&power_up.texture.height as f32 / 2.0,
-            //        ),
-            //    Color::WHITE,
-            //)
-            drawing.draw_quad(
-                (power_up.pos - player.pos).normalize() * 210.0 + player.pos,
-                Vector2 { x: 32.0, y: 32.0 },
-                Vector4 {
-                    x: 1.0,
-                    y: 1.0,
-                    z: 1.0,
-                    w: 1.0,
-                },
-                0.0,
-                Some(power_up.texture),
-            );
-        }
+        draw_edge_indicator(drawing, power_up.pos, power_up.texture);
     }
 }
@@ -0,0 +1,143 @@
+/// Points for every second the player stays alive.
+const POINTS_PER_SECOND: f32 = 10.0;
+/// How long after a kill the next one still counts towards the combo.
+const COMBO_WINDOW: f32 = 2.0;
+const MAX_COMBO_MULTIPLIER: u32 = 8;
+
+/// What destroyed an enemy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KillCause {
+    /// Shot by the player.
+    Bullet,
+    /// Flew into one of the player's parts, which hurts the player too.
+    Rammed,
+    /// Flew into another enemy.
+    Collision,
+}
+
+impl KillCause {
+    pub const ALL: [KillCause; 3] = [KillCause::Bullet, KillCause::Rammed, KillCause::Collision];
+
+    /// How much of an enemy's points a kill like this is worth. Shooting is worth the most.
+    fn share(self) -> f32 {
+        match self {
+            KillCause::Bullet => 1.0,
+            KillCause::Rammed => 0.5,
+            KillCause::Collision => 0.25,
+        }
+    }
+}
+
+/// Points for kills, with quick successive kills multiplied, plus points for time survived.
+#[derive(Clone, Debug, Default)]
+pub struct Score {
+    kill_points: u64,
+    survived: f32,
+    /// Indexed by `KillCause`.
+    kills: [u32; KillCause::ALL.len()],
+    /// Kills in a row, each within `COMBO_WINDOW` of the last.
+    combo: u32,
+    combo_time_left: f32,
+    best_combo: u32,
+}
+
+impl Score {
+    pub fn update(&mut self, dt: f32) {
+        self.survived += dt;
+        self.combo_time_left -= dt;
+        if self.combo_time_left <= 0.0 {
+            self.combo = 0;
+        }
+    }
+
+    /// Scores an enemy worth `points` being destroyed by `cause`.
+    pub fn record_kill(&mut self, points: u32, cause: KillCause) {
+        self.combo += 1;
+        self.combo_time_left = COMBO_WINDOW;
+        self.best_combo = self.best_combo.max(self.combo);
+        self.kills[cause as usize] += 1;
+        self.kill_points +=
+            (points as f32 * cause.share() * self.multiplier() as f32).round() as u64;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.kill_points + (self.survived * POINTS_PER_SECOND) as u64
+    }
+
+    pub fn kills(&self, cause: KillCause) -> u32 {
+        self.kills[cause as usize]
+    }
+
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    pub fn best_combo(&self) -> u32 {
+        self.best_combo
+    }
+
+    /// What the latest kill was multiplied by, going up with each kill in the combo.
+    pub fn multiplier(&self) -> u32 {
+        self.combo.clamp(1, MAX_COMBO_MULTIPLIER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_points() {
+        let mut score = Score::default();
+        for _ in 0..30 {
+            score.update(0.5);
+        }
+        assert_eq!(score.total(), (15.0 * POINTS_PER_SECOND) as u64);
+    }
+
+    #[test]
+    fn share_by_cause() {
+        for (cause, points) in [
+            (KillCause::Bullet, 100),
+            (KillCause::Rammed, 50),
+            (KillCause::Collision, 25),
+        ] {
+            let mut score = Score::default();
+            score.record_kill(100, cause);
+            assert_eq!(score.total(), points, "{cause:?}");
+            assert_eq!(score.kills(cause), 1);
+        }
+    }
+
+    #[test]
+    fn combo_window_expires() {
+        let mut score = Score::default();
+        score.record_kill(10, KillCause::Bullet);
+        score.update(COMBO_WINDOW - 0.5);
+        score.record_kill(10, KillCause::Bullet);
+        assert_eq!(score.combo(), 2);
+        assert_eq!(score.multiplier(), 2);
+
+        score.update(COMBO_WINDOW);
+        assert_eq!(score.combo(), 0);
+        score.record_kill(10, KillCause::Bullet);
+        assert_eq!(score.multiplier(), 1);
+        assert_eq!(score.best_combo(), 2);
+    }
+
+    #[test]
+    fn multiplier_is_capped() {
+        let mut score = Score::default();
+        let kills = MAX_COMBO_MULTIPLIER + 4;
+        for _ in 0..kills {
+            score.record_kill(10, KillCause::Bullet);
+        }
+        assert_eq!(score.combo(), kills);
+        assert_eq!(score.multiplier(), MAX_COMBO_MULTIPLIER);
+        let uncapped: u32 = (1..=MAX_COMBO_MULTIPLIER).sum();
+        assert_eq!(
+            score.total(),
+            (10 * (uncapped + 4 * MAX_COMBO_MULTIPLIER)) as u64
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use cgmath::{MetricSpace, Vector2};
+
+/// Cells about the size of the biggest ship, so most queries only look at a few cells.
+pub const COLLISION_CELL_SIZE: f32 = 64.0;
+
+/// A uniform grid of circles, for finding the circles that overlap another one without testing
+/// every pair.
+///
+/// The world has no edges, so cells are hashed into a fixed number of buckets, and the circles
+/// are sorted by bucket into one list. Each circle is in the cell its center is in, and queries
+/// look far enough around to reach the biggest circle, so every circle is found exactly once.
+pub struct SpatialGrid<T> {
+    cell_size: f32,
+    max_radius: f32,
+    entries: Vec<GridEntry<T>>,
+    /// Where each bucket's entries start in `entries`, with the end of the list at the end.
+    bucket_starts: Vec<usize>,
+}
+
+struct GridEntry<T> {
+    item: T,
+    pos: Vector2<f32>,
+    radius: f32,
+    cell: (i32, i32),
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    /// Circles that are not at a finite position can not overlap anything and are left out.
+    pub fn new(cell_size: f32, circles: impl IntoIterator<Item = (T, Vector2<f32>, f32)>) -> Self {
+        let mut entries: Vec<GridEntry<T>> = circles
+            .into_iter()
+            .filter(|(_, pos, _)| is_finite(*pos))
+            .map(|(item, pos, radius)| GridEntry {
+                item,
+                pos,
+                radius,
+                cell: cell(cell_size, pos),
+            })
+            .collect();
+
+        let bucket_count = entries.len().next_power_of_two();
+        entries.sort_by_key(|entry| bucket(entry.cell, bucket_count));
+        let mut bucket_starts = vec![0; bucket_count + 1];
+        for entry in &entries {
+            bucket_starts[bucket(entry.cell, bucket_count) + 1] += 1;
+        }
+        for index in 1..bucket_starts.len() {
+            bucket_starts[index] += bucket_starts[index - 1];
+        }
+
+        Self {
+            cell_size,
+            max_radius: entries.iter().map(|entry| entry.radius).fold(0.0, f32::max),
+            entries,
+            bucket_starts,
+        }
+    }
+
+    /// Every item whose circle overlaps the circle at `pos` with `radius`. Touching is not
+    /// overlapping.
+    pub fn overlapping(&self, pos: Vector2<f32>, radius: f32) -> impl Iterator<Item = T> + '_ {
+        let reach = radius + self.max_radius;
+        let (min, max) = if is_finite(pos) && reach.is_finite() && !self.entries.is_empty() {
+            (
+                cell(self.cell_size, pos - Vector2::new(reach, reach)),
+                cell(self.cell_size, pos + Vector2::new(reach, reach)),
+            )
+        } else {
+            // An empty range, since there is nothing to find.
+            ((0, 0), (-1, -1))
+        };
+        let bucket_count = self.bucket_starts.len() - 1;
+        (min.0..=max.0)
+            .flat_map(move |x| (min.1..=max.1).map(move |y| (x, y)))
+            .flat_map(move |cell| {
+                let bucket = bucket(cell, bucket_count);
+                self.entries[self.bucket_starts[bucket]..self.bucket_starts[bucket + 1]]
+                    .iter()
+                    .filter(move |entry| entry.cell == cell)
+            })
+            .filter(move |entry| entry.pos.distance(pos) < entry.radius + radius)
+            .map(|entry| entry.item)
+    }
+}
+
+fn cell(cell_size: f32, pos: Vector2<f32>) -> (i32, i32) {
+    (
+        (pos.x / cell_size).floor() as i32,
+        (pos.y / cell_size).floor() as i32,
+    )
+}
+
+/// `bucket_count` has to be a power of two.
+fn bucket(cell: (i32, i32), bucket_count: usize) -> usize {
+    let hash =
+        (cell.0 as u32).wrapping_mul(0x9e37_79b1) ^ (cell.1 as u32).wrapping_mul(0x85eb_ca77);
+    hash as usize & (bucket_count - 1)
+}
+
+fn is_finite(pos: Vector2<f32>) -> bool {
+    pos.x.is_finite() && pos.y.is_finite()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::game::GameRng;
+
+    fn brute_force(
+        circles: &[(usize, Vector2<f32>, f32)],
+        pos: Vector2<f32>,
+        radius: f32,
+    ) -> Vec<usize> {
+        circles
+            .iter()
+            .filter(|(_, other, other_radius)| other.distance(pos) < other_radius + radius)
+            .map(|(item, _, _)| *item)
+            .collect()
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = GameRng::seed_from_u64(12);
+        let random_pos = |rng: &mut GameRng| {
+            Vector2::new(rng.gen_range(-500.0..500.0), rng.gen_range(-500.0..500.0))
+        };
+        let circles: Vec<_> = (0..300)
+            .map(|item| (item, random_pos(&mut rng), rng.gen_range(1.0..80.0)))
+            .collect();
+        let grid = SpatialGrid::new(COLLISION_CELL_SIZE, circles.iter().copied());
+        for _ in 0..300 {
+            let pos = random_pos(&mut rng);
+            let radius = rng.gen_range(0.0..100.0);
+            let mut found: Vec<_> = grid.overlapping(pos, radius).collect();
+            found.sort();
+            assert_eq!(found, brute_force(&circles, pos, radius));
+        }
+    }
+
+    #[test]
+    fn skips_circles_that_are_not_finite() {
+        let circles = [
+            (0, Vector2::new(0.0, 0.0), 10.0),
+            (1, Vector2::new(f32::NAN, 0.0), 10.0),
+            (2, Vector2::new(0.0, f32::INFINITY), 10.0),
+        ];
+        let grid = SpatialGrid::new(COLLISION_CELL_SIZE, circles);
+        assert_eq!(
+            grid.overlapping(Vector2::new(5.0, 0.0), 1.0)
+                .collect::<Vec<_>>(),
+            [0]
+        );
+        assert_eq!(
+            grid.overlapping(Vector2::new(f32::NAN, 0.0), 1.0).count(),
+            0
+        );
+    }
+}
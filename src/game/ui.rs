@@ -1,60 +1,110 @@
-use raylib::prelude::*;
+use cgmath::{vec2, Vector2, Vector4};
 
-use crate::Player;
+use crate::renderer::{
+    text::{Anchor, TextStyle},
+    texture::TextureId,
+    Rendering2D,
+};
 
-pub fn draw_timer(d: &mut RaylibDrawHandle, time: f32, screenwidth: i32) {
-    d.draw_text(
-        format!("Time: {:.1}", time).as_str(),
-        screenwidth / 2 - 50,
-        10,
-        36,
-        Color::WHITE,
+use super::{colorlerp, Player, Score};
+
+const WHITE: Vector4<f32> = Vector4::new(1.0, 1.0, 1.0, 1.0);
+const HEALTHY_COLOR: Vector4<f32> = Vector4::new(0.2, 0.9, 0.3, 1.0);
+const BROKEN_COLOR: Vector4<f32> = Vector4::new(0.9, 0.15, 0.1, 1.0);
+const BAR_BACKGROUND_COLOR: Vector4<f32> = Vector4::new(0.1, 0.1, 0.1, 0.7);
+
+/// Distance of the HUD from the edges of the screen.
+const MARGIN: f32 = 20.0;
+const PANEL_WIDTH: f32 = 220.0;
+const BAR_HEIGHT: f32 = 12.0;
+
+pub fn draw_timer(drawing: &mut Rendering2D<'_, '_>, time: f32) {
+    let position = drawing.screen_position(Anchor::Top, vec2(0.0, -MARGIN));
+    drawing.draw_screen_text(
+        &format!("Time: {:.1}", time),
+        position,
+        &TextStyle::new(28.0, WHITE).anchored(Anchor::Top),
     );
 }
 
-pub fn draw_part_health(
-    d: &mut RaylibDrawHandle,
-    player: &Player,
-    default_font: &WeakFont,
-    screenwidth: i32,
-) {
-    let longest_name_len = player
-        .parts
-        .iter()
-        .map(|part| part.name.len())
-        .max()
-        .unwrap_or(0);
-
-    for part_index in 0..player.parts.len() {
-        let part = &player.parts[part_index];
-        let font_size = 18.0;
-        let spacing = 5.0;
-        let name_length = longest_name_len as f32 * spacing;
-        d.draw_text_pro(
-            &default_font,
-            part.name.as_str(),
-            Vector2 {
-                x: screenwidth as f32 - name_length as f32 * 3.0,
-                y: 10.0 + (part_index as f32 * (font_size + 15.0)),
-            },
-            Vector2 { x: 0.0, y: 0.0 },
+/// The score under the timer, with the combo multiplier while a combo is going.
+pub fn draw_score(drawing: &mut Rendering2D<'_, '_>, score: &Score) {
+    let position = drawing.screen_position(Anchor::Top, vec2(0.0, -MARGIN - 34.0));
+    drawing.draw_screen_text(
+        &format!("Score: {}", score.total()),
+        position,
+        &TextStyle::new(22.0, WHITE).anchored(Anchor::Top),
+    );
+    if score.combo() > 1 {
+        drawing.draw_screen_text(
+            &format!("x{}", score.multiplier()),
+            position - vec2(0.0, 26.0),
+            &TextStyle::new(20.0, Vector4::new(1.0, 0.8, 0.2, 1.0)).anchored(Anchor::Top),
+        );
+    }
+}
+
+/// A bar for each part in the top right corner, going from green to red as the part breaks.
+pub fn draw_part_health(drawing: &mut Rendering2D<'_, '_>, player: &Player) {
+    let font_size = 14.0;
+    let row_height = font_size + BAR_HEIGHT + 18.0;
+    let top_right = drawing.screen_position(Anchor::TopRight, vec2(-MARGIN, -MARGIN));
+
+    for (part_index, part) in player.parts.iter().enumerate() {
+        let health = (part.health / part.starting_health).clamp(0.0, 1.0);
+        let top = top_right.y - part_index as f32 * row_height;
+        let left = top_right.x - PANEL_WIDTH;
+
+        drawing.draw_screen_text(
+            &part.name,
+            vec2(left, top),
+            &TextStyle::new(font_size, WHITE),
+        );
+        drawing.draw_screen_text(
+            &format!("{:.0}%", health * 100.0),
+            vec2(top_right.x, top),
+            &TextStyle::new(font_size, WHITE).anchored(Anchor::TopRight),
+        );
+
+        let bar_y = top - font_size - 6.0 - BAR_HEIGHT / 2.0;
+        drawing.draw_screen_quad(
+            vec2(left + PANEL_WIDTH / 2.0, bar_y),
+            vec2(PANEL_WIDTH, BAR_HEIGHT),
+            BAR_BACKGROUND_COLOR,
             0.0,
-            font_size,
-            spacing,
-            Color::WHITE,
+            None,
         );
-        d.draw_text_pro(
-            &default_font,
-            format!("Health: {:.1}%", part.health / part.starting_health * 100.0).as_str(),
-            Vector2 {
-                x: screenwidth as f32 - name_length as f32 * 3.0 + 10.0,
-                y: 28.0 + (part_index as f32 * (font_size + 15.0)),
-            },
-            Vector2 { x: 0.0, y: 0.0 },
+        drawing.draw_screen_quad(
+            vec2(left + PANEL_WIDTH * health / 2.0, bar_y),
+            vec2(PANEL_WIDTH * health, BAR_HEIGHT),
+            colorlerp(BROKEN_COLOR, HEALTHY_COLOR, health),
             0.0,
-            font_size / 1.1,
-            spacing / 1.1,
-            Color::WHITE,
+            None,
         );
     }
 }
+
+/// Points at something the camera can not see from the edge of the screen, or draws nothing
+/// if it is on screen.
+pub fn draw_edge_indicator(
+    drawing: &mut Rendering2D<'_, '_>,
+    target: Vector2<f32>,
+    texture: TextureId,
+) {
+    let size = 32.0;
+    let position = drawing.world_to_screen(target);
+    let half_screen = drawing.screen_size() / 2.0;
+    if position.x.abs() <= half_screen.x && position.y.abs() <= half_screen.y {
+        return;
+    }
+    // Slide the target towards the middle of the screen until the indicator fits.
+    let inset = half_screen - vec2(MARGIN + size / 2.0, MARGIN + size / 2.0);
+    let scale = (inset.x / position.x.abs()).min(inset.y / position.y.abs());
+    drawing.draw_screen_quad(
+        position * scale,
+        vec2(size, size),
+        WHITE,
+        0.0,
+        Some(texture),
+    );
+}
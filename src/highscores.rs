@@ -0,0 +1,221 @@
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// How many scores the table keeps.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+/// One finished game worth remembering.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u64,
+    /// Seconds survived.
+    pub time: f32,
+    pub seed: u64,
+    pub ship: String,
+}
+
+#[derive(Serialize)]
+struct HighScoreFile<'a> {
+    scores: &'a [HighScore],
+}
+
+/// Entries that fail to parse are skipped rather than losing the whole table.
+#[derive(Deserialize)]
+struct LoadedHighScoreFile {
+    scores: Vec<LoadedHighScore>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LoadedHighScore {
+    Valid(HighScore),
+    Invalid(IgnoredAny),
+}
+
+/// The best scores so far, highest first, kept in a file if there is one.
+#[derive(Clone, Debug, Default)]
+pub struct HighScores {
+    path: Option<PathBuf>,
+    scores: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Where the high scores are kept in the user's data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|data| data.join("spaceshooter").join("highscores.ron"))
+    }
+
+    /// Loads the table from `path`, which is also where it gets saved. A missing file gives an
+    /// empty table, and so does one that cannot be read, after moving it out of the way so
+    /// saving does not overwrite it.
+    pub fn load(path: PathBuf) -> Self {
+        let scores = if path.exists() {
+            Self::read(&path).unwrap_or_else(|error| {
+                let backup = path.with_extension("ron.bad");
+                eprintln!(
+                    "{}: {error}, starting with no high scores (the old file is kept as {})",
+                    path.display(),
+                    backup.display()
+                );
+                if let Err(error) = fs::rename(&path, &backup) {
+                    eprintln!("failed to move {}: {error}", path.display());
+                }
+                vec![]
+            })
+        } else {
+            vec![]
+        };
+        let mut high_scores = Self {
+            path: Some(path),
+            scores,
+        };
+        high_scores.tidy();
+        high_scores
+    }
+
+    fn read(path: &Path) -> Result<Vec<HighScore>, String> {
+        let source = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let file: LoadedHighScoreFile =
+            ron::from_str(&source).map_err(|error| error.to_string())?;
+        let total = file.scores.len();
+        let scores: Vec<_> = file
+            .scores
+            .into_iter()
+            .filter_map(|score| match score {
+                LoadedHighScore::Valid(score) if score.time.is_finite() => Some(score),
+                _ => None,
+            })
+            .collect();
+        if scores.len() < total {
+            eprintln!(
+                "{}: skipped {} broken high scores",
+                path.display(),
+                total - scores.len()
+            );
+        }
+        Ok(scores)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let file = HighScoreFile {
+            scores: &self.scores,
+        };
+        let source = ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, source)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn scores(&self) -> &[HighScore] {
+        &self.scores
+    }
+
+    /// Puts `score` in the table if it is good enough, returning its place counting from 0.
+    /// Ties go below the scores that were there first.
+    pub fn add(&mut self, score: HighScore) -> Option<usize> {
+        let place = self
+            .scores
+            .iter()
+            .position(|other| other.score < score.score)
+            .unwrap_or(self.scores.len());
+        if place >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.scores.insert(place, score);
+        self.scores.truncate(MAX_HIGH_SCORES);
+        Some(place)
+    }
+
+    /// Sorts a table that came from a file, in case it was edited by hand.
+    fn tidy(&mut self) {
+        self.scores.sort_by_key(|score| Reverse(score.score));
+        self.scores.truncate(MAX_HIGH_SCORES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test's files.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "spaceshooter-highscores-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn score(name: &str, score: u64) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            time: 10.0,
+            seed: 1,
+            ship: "Fighter".to_string(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = test_dir("round_trip").join("highscores.ron");
+        let mut high_scores = HighScores::load(path.clone());
+        assert_eq!(high_scores.add(score("A", 5)), Some(0));
+        assert_eq!(high_scores.add(score("B", 10)), Some(0));
+        high_scores.save().unwrap();
+        assert_eq!(HighScores::load(path).scores(), high_scores.scores());
+    }
+
+    #[test]
+    fn corrupt_file_is_kept_aside() {
+        let path = test_dir("corrupt").join("highscores.ron");
+        fs::write(&path, "(scores: [(name: ").unwrap();
+        let high_scores = HighScores::load(path.clone());
+        assert!(high_scores.scores().is_empty());
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(path.with_extension("ron.bad")).unwrap(),
+            "(scores: [(name: "
+        );
+    }
+
+    #[test]
+    fn broken_entries_are_skipped() {
+        let path = test_dir("broken_entries").join("highscores.ron");
+        fs::write(
+            &path,
+            r#"(scores: [
+                (name: "A", score: 5, time: 1.0, seed: 1, ship: "Fighter"),
+                (name: "B", score: "lots"),
+                (name: "C", score: 7, time: inf, seed: 1, ship: "Fighter"),
+                (name: "D", score: 9, time: 2.0, seed: 1, ship: "Fighter"),
+            ])"#,
+        )
+        .unwrap();
+        let high_scores = HighScores::load(path.clone());
+        let names: Vec<_> = high_scores
+            .scores()
+            .iter()
+            .map(|score| &score.name)
+            .collect();
+        assert_eq!(names, ["D", "A"]);
+        assert!(path.exists());
+    }
+}
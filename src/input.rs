@@ -0,0 +1,258 @@
+use bindings::{Action, Bindings, Control};
+use gamepad::{GamepadBackend, GamepadInput};
+use std::collections::{HashSet, VecDeque};
+use winit::{
+    event::{ElementState, KeyEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+pub mod bindings;
+pub mod gamepad;
+
+/// What the player wants to do this frame, independent of where the input came from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayerInput {
+    /// How hard to turn left, from 0 to 1.
+    pub turn_left: f32,
+    /// How hard to turn right, from 0 to 1.
+    pub turn_right: f32,
+    /// How hard the engines push, from 0 to 1. The ship flies at full thrust unless the player
+    /// brakes.
+    pub thrust: f32,
+    /// Fire the guns even when nothing is lined up.
+    pub fire: bool,
+}
+
+impl Default for PlayerInput {
+    fn default() -> Self {
+        Self {
+            turn_left: 0.0,
+            turn_right: 0.0,
+            thrust: 1.0,
+            fire: false,
+        }
+    }
+}
+
+impl PlayerInput {
+    /// Combines the input of two devices used at the same time. Braking on either one brakes.
+    pub fn combine(self, other: PlayerInput) -> PlayerInput {
+        PlayerInput {
+            turn_left: f32::max(self.turn_left, other.turn_left),
+            turn_right: f32::max(self.turn_right, other.turn_right),
+            thrust: f32::min(self.thrust, other.thrust),
+            fire: self.fire || other.fire,
+        }
+    }
+}
+
+pub trait InputSource {
+    fn player_input(&mut self) -> PlayerInput;
+}
+
+/// Keyboard state built up from the window's key events, so only keys pressed while the window
+/// is focused count.
+#[derive(Default)]
+pub struct KeyboardInput {
+    pressed: HashSet<KeyCode>,
+}
+
+impl KeyboardInput {
+    pub fn handle_key_event(&mut self, event: &KeyEvent) {
+        let PhysicalKey::Code(key) = event.physical_key else {
+            return;
+        };
+        match event.state {
+            ElementState::Pressed => {
+                self.pressed.insert(key);
+            }
+            ElementState::Released => {
+                self.pressed.remove(&key);
+            }
+        }
+    }
+
+    /// Forget every held key, e.g. when the window loses focus and will not see the releases.
+    pub fn release_all(&mut self) {
+        self.pressed.clear();
+    }
+
+    pub fn is_pressed(&self, key: KeyCode) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    pub fn is_action_pressed(&self, bindings: &Bindings, action: Action) -> bool {
+        bindings
+            .keys(action)
+            .iter()
+            .any(|key| self.is_pressed(*key))
+    }
+
+    pub fn player_input(&self, bindings: &Bindings) -> PlayerInput {
+        let pressed = |action| {
+            if self.is_action_pressed(bindings, action) {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        PlayerInput {
+            turn_left: pressed(Action::TurnLeft),
+            turn_right: pressed(Action::TurnRight),
+            thrust: 1.0 - pressed(Action::Brake),
+            fire: self.is_action_pressed(bindings, Action::Fire),
+        }
+    }
+}
+
+/// What a key or button press did to a rebind in progress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebindEvent {
+    Bound(Action, Control),
+    /// The control belongs to a menu action, which would lose it. The rebind waits for another
+    /// press.
+    Refused(Control, Action),
+    /// A control bound to `Action::Pause` was pressed, ending the rebind early.
+    Cancelled,
+}
+
+/// The keyboard and gamepad together, sharing one set of bindings that can be changed while
+/// the game runs.
+pub struct Controls {
+    bindings: Bindings,
+    keyboard: KeyboardInput,
+    gamepad: GamepadInput,
+    rebinding: VecDeque<Action>,
+}
+
+impl Controls {
+    pub fn new(bindings: Bindings, gamepad: Box<dyn GamepadBackend>) -> Self {
+        Self {
+            bindings,
+            keyboard: KeyboardInput::default(),
+            gamepad: GamepadInput::new(gamepad),
+            rebinding: VecDeque::new(),
+        }
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    /// Binds the next keys or buttons pressed to `actions` in order instead of treating them as
+    /// input.
+    pub fn start_rebind(&mut self, actions: &[Action]) {
+        self.rebinding = actions.iter().copied().collect();
+    }
+
+    /// The action the next press will be bound to, if a rebind is in progress.
+    pub fn rebinding(&self) -> Option<Action> {
+        self.rebinding.front().copied()
+    }
+
+    /// Tracks held keys, or uses a press for the rebind in progress. Held keys repeating don't
+    /// count as presses.
+    pub fn handle_key_event(&mut self, event: &KeyEvent) -> Option<RebindEvent> {
+        let PhysicalKey::Code(key) = event.physical_key else {
+            return None;
+        };
+        if self.rebinding().is_some() && event.state == ElementState::Pressed {
+            return (!event.repeat).then(|| self.rebind(Control::Key(key)));
+        }
+        self.keyboard.handle_key_event(event);
+        None
+    }
+
+    /// The action a key press triggers once, like pausing, ignoring key repeat and rebinds.
+    pub fn triggered_key_action(&self, event: &KeyEvent) -> Option<Action> {
+        let PhysicalKey::Code(key) = event.physical_key else {
+            return None;
+        };
+        if event.state != ElementState::Pressed || event.repeat || self.rebinding().is_some() {
+            return None;
+        }
+        Action::ALL
+            .into_iter()
+            .find(|action| self.bindings.keys(*action).contains(&key))
+    }
+
+    /// Reads the gamepad, using a newly pressed button for the rebind in progress.
+    pub fn poll_gamepad(&mut self) -> Option<RebindEvent> {
+        self.gamepad.poll();
+        self.rebinding()?;
+        let button = self.gamepad.newly_pressed()?;
+        Some(self.rebind(Control::Button(button)))
+    }
+
+    /// Actions triggered by gamepad buttons pressed between the last two polls, none during a
+    /// rebind.
+    pub fn triggered_gamepad_actions(&self) -> Vec<Action> {
+        if self.rebinding().is_some() {
+            return vec![];
+        }
+        Action::ALL
+            .into_iter()
+            .filter(|action| self.gamepad.is_action_triggered(&self.bindings, *action))
+            .collect()
+    }
+
+    /// Forget every held key, e.g. when the window loses focus and will not see the releases.
+    pub fn release_all(&mut self) {
+        self.keyboard.release_all();
+    }
+
+    fn rebind(&mut self, control: Control) -> RebindEvent {
+        let owners = self.bindings.actions_for(control);
+        if owners.contains(&Action::Pause) {
+            self.rebinding.clear();
+            return RebindEvent::Cancelled;
+        }
+        let action = self.rebinding[0];
+        // A control can be shared with a menu action, like Fire and Confirm both on South by
+        // default, but only if it already was.
+        if !owners.contains(&action) {
+            let menu_owner = owners
+                .into_iter()
+                .find(|owner| !Action::GAMEPLAY.contains(owner));
+            if let Some(owner) = menu_owner {
+                return RebindEvent::Refused(control, owner);
+            }
+            self.bindings.rebind(action, control);
+        }
+        self.rebinding.pop_front();
+        RebindEvent::Bound(action, control)
+    }
+}
+
+impl InputSource for Controls {
+    fn player_input(&mut self) -> PlayerInput {
+        self.keyboard
+            .player_input(&self.bindings)
+            .combine(self.gamepad.player_input(&self.bindings))
+    }
+}
+
+/// Plays back a fixed list of inputs, one per call, then keeps returning the default input.
+#[derive(Default)]
+pub struct ScriptedInput {
+    inputs: Vec<PlayerInput>,
+    index: usize,
+}
+
+impl ScriptedInput {
+    pub fn new(inputs: Vec<PlayerInput>) -> Self {
+        Self { inputs, index: 0 }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.index >= self.inputs.len()
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn player_input(&mut self) -> PlayerInput {
+        let input = self.inputs.get(self.index).copied().unwrap_or_default();
+        self.index += 1;
+        input
+    }
+}
@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use winit::keyboard::KeyCode;
+
+use crate::game::{load_definition, DataError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    TurnLeft,
+    TurnRight,
+    Fire,
+    Brake,
+    Pause,
+    Confirm,
+    Restart,
+    ToggleDebug,
+    HighScores,
+    Options,
+    MenuUp,
+    MenuDown,
+    Rebind,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Fire,
+        Action::Brake,
+        Action::Pause,
+        Action::Confirm,
+        Action::Restart,
+        Action::ToggleDebug,
+        Action::HighScores,
+        Action::Options,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::Rebind,
+    ];
+
+    /// The actions that steer the ship, which are the ones the options screen can rebind.
+    /// Menu actions stay as they are so the menus can't be locked out.
+    pub const GAMEPLAY: [Action; 4] = [
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Fire,
+        Action::Brake,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::TurnLeft => "Turn Left",
+            Action::TurnRight => "Turn Right",
+            Action::Fire => "Fire",
+            Action::Brake => "Brake",
+            Action::Pause => "Pause",
+            Action::Confirm => "Confirm",
+            Action::Restart => "Restart",
+            Action::ToggleDebug => "Toggle Debug",
+            Action::HighScores => "High Scores",
+            Action::Options => "Options",
+            Action::MenuUp => "Menu Up",
+            Action::MenuDown => "Menu Down",
+            Action::Rebind => "Change Controls",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// A key or gamepad button that can be bound to an action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Key(KeyCode),
+    Button(GamepadButton),
+}
+
+impl Control {
+    pub fn name(self) -> String {
+        match self {
+            Control::Key(key) => key_name(key),
+            Control::Button(button) => format!("{button:?} on the gamepad"),
+        }
+    }
+}
+
+/// Which keys and gamepad buttons trigger each action.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bindings {
+    pub keyboard: BTreeMap<Action, Vec<KeyCode>>,
+    pub gamepad: BTreeMap<Action, Vec<GamepadButton>>,
+    /// How far the steering stick has to move before the ship starts turning, from 0 to 1.
+    pub stick_deadzone: f32,
+}
+
+/// A short name for a key, like "A" for `KeyCode::KeyA`.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    ["Key", "Digit"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .map_or(name.clone(), str::to_string)
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            keyboard: BTreeMap::from([
+                (Action::TurnLeft, vec![KeyCode::KeyA]),
+                (Action::TurnRight, vec![KeyCode::KeyD]),
+                (Action::Fire, vec![KeyCode::Space]),
+                (Action::Brake, vec![KeyCode::KeyS]),
+                (Action::Pause, vec![KeyCode::Escape]),
+                (Action::Confirm, vec![KeyCode::Enter]),
+                (Action::Restart, vec![KeyCode::KeyR]),
+                (Action::ToggleDebug, vec![KeyCode::F3]),
+                (Action::HighScores, vec![KeyCode::Tab]),
+                (Action::Options, vec![KeyCode::KeyO]),
+                (Action::MenuUp, vec![KeyCode::ArrowUp]),
+                (Action::MenuDown, vec![KeyCode::ArrowDown]),
+                (Action::Rebind, vec![KeyCode::F1]),
+            ]),
+            gamepad: BTreeMap::from([
+                (Action::TurnLeft, vec![GamepadButton::DPadLeft]),
+                (Action::TurnRight, vec![GamepadButton::DPadRight]),
+                (Action::Fire, vec![GamepadButton::South]),
+                (Action::Brake, vec![GamepadButton::East]),
+                (Action::Pause, vec![GamepadButton::Start]),
+                (Action::Confirm, vec![GamepadButton::South]),
+                (Action::Restart, vec![GamepadButton::Select]),
+                (Action::HighScores, vec![GamepadButton::North]),
+                (Action::Options, vec![GamepadButton::West]),
+                (Action::MenuUp, vec![GamepadButton::DPadUp]),
+                (Action::MenuDown, vec![GamepadButton::DPadDown]),
+            ]),
+            stick_deadzone: 0.15,
+        }
+    }
+}
+
+impl Bindings {
+    /// Where the bindings are kept in the user's config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config| config.join("spaceshooter").join("bindings.ron"))
+    }
+
+    /// Loads the bindings from `path`. Actions the file does not mention keep their default
+    /// bindings, and a missing file gives the defaults.
+    pub fn load(path: &Path) -> Result<Self, DataError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let mut bindings: Self = load_definition(path)?;
+        let defaults = Self::default();
+        for (action, keys) in defaults.keyboard {
+            bindings.keyboard.entry(action).or_insert(keys);
+        }
+        for (action, buttons) in defaults.gamepad {
+            bindings.gamepad.entry(action).or_insert(buttons);
+        }
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, source)
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keyboard.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn buttons(&self, action: Action) -> &[GamepadButton] {
+        self.gamepad.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every key bound to `action` by name, like "A or Space".
+    pub fn key_names(&self, action: Action) -> String {
+        let names: Vec<_> = self.keys(action).iter().map(|key| key_name(*key)).collect();
+        if names.is_empty() {
+            "nothing".to_string()
+        } else {
+            names.join(" or ")
+        }
+    }
+
+    /// Every action `control` is bound to.
+    pub fn actions_for(&self, control: Control) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| match control {
+                Control::Key(key) => self.keys(*action).contains(&key),
+                Control::Button(button) => self.buttons(*action).contains(&button),
+            })
+            .collect()
+    }
+
+    pub fn rebind(&mut self, action: Action, control: Control) {
+        match control {
+            Control::Key(key) => self.rebind_key(action, key),
+            Control::Button(button) => self.rebind_button(action, button),
+        }
+    }
+
+    /// Makes `key` the only key for `action`, taking it away from any other action.
+    pub fn rebind_key(&mut self, action: Action, key: KeyCode) {
+        for keys in self.keyboard.values_mut() {
+            keys.retain(|bound| *bound != key);
+        }
+        self.keyboard.insert(action, vec![key]);
+    }
+
+    /// Makes `button` the only button for `action`, taking it away from any other action.
+    pub fn rebind_button(&mut self, action: Action, button: GamepadButton) {
+        for buttons in self.gamepad.values_mut() {
+            buttons.retain(|bound| *bound != button);
+        }
+        self.gamepad.insert(action, vec![button]);
+    }
+}
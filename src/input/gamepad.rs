@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+
+use super::{
+    bindings::{Action, Bindings, GamepadButton},
+    PlayerInput,
+};
+
+/// A snapshot of the gamepad the player is using.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    /// Sideways deflection of the steering stick, from -1 (left) to 1 (right).
+    pub steering: f32,
+    pub pressed: HashSet<GamepadButton>,
+}
+
+/// Where gamepad state comes from, so a real controller library can be swapped for a virtual
+/// device.
+pub trait GamepadBackend {
+    /// Processes pending controller events and returns the state of the active gamepad, or
+    /// `None` when no gamepad is connected.
+    fn poll(&mut self) -> Option<GamepadState>;
+}
+
+/// A gamepad whose state is set directly, for tests and scripted play.
+#[derive(Default)]
+pub struct VirtualGamepad {
+    pub state: Option<GamepadState>,
+}
+
+impl GamepadBackend for VirtualGamepad {
+    fn poll(&mut self) -> Option<GamepadState> {
+        self.state.clone()
+    }
+}
+
+#[cfg(feature = "gamepad")]
+pub struct GilrsGamepad {
+    gilrs: gilrs::Gilrs,
+    active: Option<gilrs::GamepadId>,
+}
+
+#[cfg(feature = "gamepad")]
+impl GilrsGamepad {
+    /// The error is boxed since `gilrs::Error` is large.
+    pub fn new() -> Result<Self, Box<gilrs::Error>> {
+        Ok(Self {
+            gilrs: gilrs::Gilrs::new()?,
+            active: None,
+        })
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadBackend for GilrsGamepad {
+    fn poll(&mut self) -> Option<GamepadState> {
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                gilrs::EventType::Disconnected if self.active == Some(event.id) => {
+                    self.active = None;
+                }
+                gilrs::EventType::Disconnected => {}
+                _ => self.active = Some(event.id),
+            }
+        }
+
+        let gamepad = match self.active {
+            Some(id) => self.gilrs.connected_gamepad(id)?,
+            None => self.gilrs.gamepads().next()?.1,
+        };
+
+        const BUTTONS: [(gilrs::Button, GamepadButton); 14] = [
+            (gilrs::Button::South, GamepadButton::South),
+            (gilrs::Button::East, GamepadButton::East),
+            (gilrs::Button::North, GamepadButton::North),
+            (gilrs::Button::West, GamepadButton::West),
+            (gilrs::Button::LeftTrigger, GamepadButton::LeftBumper),
+            (gilrs::Button::RightTrigger, GamepadButton::RightBumper),
+            (gilrs::Button::LeftTrigger2, GamepadButton::LeftTrigger),
+            (gilrs::Button::RightTrigger2, GamepadButton::RightTrigger),
+            (gilrs::Button::Select, GamepadButton::Select),
+            (gilrs::Button::Start, GamepadButton::Start),
+            (gilrs::Button::DPadUp, GamepadButton::DPadUp),
+            (gilrs::Button::DPadDown, GamepadButton::DPadDown),
+            (gilrs::Button::DPadLeft, GamepadButton::DPadLeft),
+            (gilrs::Button::DPadRight, GamepadButton::DPadRight),
+        ];
+
+        Some(GamepadState {
+            steering: gamepad.value(gilrs::Axis::LeftStickX),
+            pressed: BUTTONS
+                .iter()
+                .filter(|(button, _)| gamepad.is_pressed(*button))
+                .map(|(_, button)| *button)
+                .collect(),
+        })
+    }
+}
+
+/// The gamepad as of the last two polls, so presses can be told apart from held buttons.
+pub struct GamepadInput {
+    backend: Box<dyn GamepadBackend>,
+    state: Option<GamepadState>,
+    previous_state: Option<GamepadState>,
+}
+
+impl GamepadInput {
+    pub fn new(backend: Box<dyn GamepadBackend>) -> Self {
+        Self {
+            backend,
+            state: None,
+            previous_state: None,
+        }
+    }
+
+    pub fn poll(&mut self) {
+        self.previous_state = self.state.take();
+        self.state = self.backend.poll();
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn is_action_pressed(&self, bindings: &Bindings, action: Action) -> bool {
+        is_action_pressed_in(self.state.as_ref(), bindings, action)
+    }
+
+    /// Whether `action` was pressed between the last two polls, for actions that happen once
+    /// per press like pausing.
+    pub fn is_action_triggered(&self, bindings: &Bindings, action: Action) -> bool {
+        self.is_action_pressed(bindings, action)
+            && !is_action_pressed_in(self.previous_state.as_ref(), bindings, action)
+    }
+
+    /// A button pressed between the last two polls, whatever it is bound to.
+    pub fn newly_pressed(&self) -> Option<GamepadButton> {
+        let state = self.state.as_ref()?;
+        let previous = self.previous_state.as_ref();
+        let mut pressed: Vec<_> = state
+            .pressed
+            .iter()
+            .filter(|button| !previous.is_some_and(|previous| previous.pressed.contains(button)))
+            .copied()
+            .collect();
+        // Several in one poll are rare, but the choice shouldn't depend on hash order.
+        pressed.sort();
+        pressed.first().copied()
+    }
+
+    /// Steering with the dead zone removed and the rest rescaled, so leaving the dead zone
+    /// starts from zero turn rate.
+    fn steering(&self, bindings: &Bindings) -> f32 {
+        let Some(state) = &self.state else {
+            return 0.0;
+        };
+        let deadzone = bindings.stick_deadzone.clamp(0.0, 0.99);
+        let magnitude = state.steering.abs().min(1.0);
+        if magnitude <= deadzone {
+            0.0
+        } else {
+            (magnitude - deadzone) / (1.0 - deadzone) * state.steering.signum()
+        }
+    }
+
+    pub fn player_input(&self, bindings: &Bindings) -> PlayerInput {
+        let steering = self.steering(bindings);
+        let button = |action| {
+            if self.is_action_pressed(bindings, action) {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        PlayerInput {
+            turn_left: f32::max(-steering, button(Action::TurnLeft)),
+            turn_right: f32::max(steering, button(Action::TurnRight)),
+            thrust: 1.0 - button(Action::Brake),
+            fire: self.is_action_pressed(bindings, Action::Fire),
+        }
+    }
+}
+
+fn is_action_pressed_in(state: Option<&GamepadState>, bindings: &Bindings, action: Action) -> bool {
+    let Some(state) = state else {
+        return false;
+    };
+    bindings
+        .buttons(action)
+        .iter()
+        .any(|button| state.pressed.contains(button))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_input(steering: f32, pressed: &[GamepadButton]) -> PlayerInput {
+        let mut gamepad = GamepadInput::new(Box::new(VirtualGamepad {
+            state: Some(GamepadState {
+                steering,
+                pressed: pressed.iter().copied().collect(),
+            }),
+        }));
+        gamepad.poll();
+        gamepad.player_input(&Bindings::default())
+    }
+
+    fn assert_turn(input: PlayerInput, turn_left: f32, turn_right: f32) {
+        assert!((input.turn_left - turn_left).abs() < 1e-6, "{input:?}");
+        assert!((input.turn_right - turn_right).abs() < 1e-6, "{input:?}");
+    }
+
+    #[test]
+    fn dead_zone() {
+        assert_turn(player_input(0.0, &[]), 0.0, 0.0);
+        assert_turn(player_input(0.15, &[]), 0.0, 0.0);
+        assert_turn(player_input(-0.1, &[]), 0.0, 0.0);
+    }
+
+    #[test]
+    fn rescaled_past_the_dead_zone() {
+        assert_turn(player_input(0.575, &[]), 0.0, 0.5);
+        assert_turn(player_input(-0.575, &[]), 0.5, 0.0);
+        assert_turn(player_input(1.0, &[]), 0.0, 1.0);
+        assert_turn(player_input(-3.0, &[]), 1.0, 0.0);
+    }
+
+    #[test]
+    fn buttons_turn_fully() {
+        let input = player_input(0.575, &[GamepadButton::DPadLeft, GamepadButton::South]);
+        assert_turn(input, 1.0, 0.5);
+        assert!(input.fire);
+        assert_eq!(input.thrust, 1.0);
+        assert_eq!(player_input(0.0, &[GamepadButton::East]).thrust, 0.0);
+    }
+
+    #[test]
+    fn disconnected() {
+        let mut gamepad = GamepadInput::new(Box::new(VirtualGamepad::default()));
+        gamepad.poll();
+        assert!(!gamepad.is_connected());
+        assert_eq!(
+            gamepad.player_input(&Bindings::default()),
+            PlayerInput::default()
+        );
+    }
+}
@@ -0,0 +1,12 @@
+#![deny(rust_2018_idioms)]
+
+pub mod always_some;
+pub mod audio;
+pub mod game;
+pub mod highscores;
+pub mod input;
+pub mod renderer;
+pub mod replay;
+pub mod session;
+pub mod settings;
+pub mod timestep;
@@ -1,36 +1,470 @@
 #![deny(rust_2018_idioms)]
 
-use game::Game;
-use renderer::{FrameRendering, Renderer};
+use spaceshooter::{
+    audio::{Audio, AudioBackend, NullAudio},
+    game::{Assets, Game, KillCause, DEFAULT_SHIP},
+    highscores::HighScores,
+    input::{
+        bindings::{Action, Bindings},
+        gamepad::{GamepadBackend, VirtualGamepad},
+        Controls, InputSource, PlayerInput, RebindEvent, ScriptedInput,
+    },
+    renderer::{texture::HeadlessTextures, FrameRendering, Renderer},
+    replay::Replay,
+    session::{Session, State},
+    settings::{Settings, WindowMode},
+    timestep::FixedTimestep,
+};
 use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant},
 };
 use winit::{
+    dpi::PhysicalSize,
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    window::{Fullscreen, Window, WindowBuilder},
 };
 
-pub mod always_some;
-mod game;
-pub mod renderer;
+struct Options {
+    ship: String,
+    seed: u64,
+    bindings: Option<PathBuf>,
+    settings: Option<PathBuf>,
+    high_scores: Option<PathBuf>,
+    name: String,
+    headless: bool,
+    duration: f32,
+    tick_rate: f32,
+    max_catch_up: u32,
+    record: Option<PathBuf>,
+    replay: Option<Replay>,
+    cpu_particals: bool,
+    mute: bool,
+}
+
+impl Options {
+    fn parse() -> Result<Self, String> {
+        let mut options = Options {
+            ship: DEFAULT_SHIP.to_string(),
+            seed: rand::random(),
+            bindings: Bindings::default_path(),
+            settings: Settings::default_path(),
+            high_scores: HighScores::default_path(),
+            name: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "Player".to_string()),
+            headless: false,
+            duration: 60.0,
+            tick_rate: 60.0,
+            max_catch_up: 8,
+            record: None,
+            replay: None,
+            cpu_particals: false,
+            mute: false,
+        };
+        let mut replay_path = None;
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--ship" => {
+                    options.ship = args.next().ok_or("`--ship` expects a ship name")?;
+                }
+                "--seed" => options.seed = parse_whole_number(args.next(), "--seed")?,
+                "--bindings" => {
+                    let path = args.next().ok_or("`--bindings` expects a file path")?;
+                    options.bindings = Some(PathBuf::from(path));
+                }
+                "--settings" => {
+                    let path = args.next().ok_or("`--settings` expects a file path")?;
+                    options.settings = Some(PathBuf::from(path));
+                }
+                "--high-scores" => {
+                    let path = args.next().ok_or("`--high-scores` expects a file path")?;
+                    options.high_scores = Some(PathBuf::from(path));
+                }
+                "--name" => options.name = args.next().ok_or("`--name` expects a name")?,
+                "--record" => {
+                    let path = args.next().ok_or("`--record` expects a file path")?;
+                    options.record = Some(PathBuf::from(path));
+                }
+                "--replay" => {
+                    let path = args.next().ok_or("`--replay` expects a file path")?;
+                    replay_path = Some(PathBuf::from(path));
+                }
+                "--headless" => options.headless = true,
+                "--cpu-particals" => options.cpu_particals = true,
+                "--mute" => options.mute = true,
+                "--duration" => options.duration = parse_number(args.next(), "--duration")?,
+                "--tick-rate" => options.tick_rate = parse_number(args.next(), "--tick-rate")?,
+                "--max-catch-up" => {
+                    options.max_catch_up = parse_whole_number(args.next(), "--max-catch-up")?;
+                    if options.max_catch_up == 0 {
+                        return Err("`--max-catch-up` must be at least 1".to_string());
+                    }
+                }
+                _ => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        if let Some(path) = replay_path {
+            let replay = Replay::load(&path)
+                .map_err(|error| format!("failed to load replay {}: {error}", path.display()))?;
+            options.seed = replay.seed;
+            options.ship = replay.ship.clone();
+            options.tick_rate = replay.tick_rate;
+            options.replay = Some(replay);
+        }
+        Ok(options)
+    }
+}
+
+fn parse_number(value: Option<String>, arg: &str) -> Result<f32, String> {
+    let value = value.ok_or_else(|| format!("`{arg}` expects a number"))?;
+    match value.parse::<f32>() {
+        Ok(number) if number > 0.0 => Ok(number),
+        _ => Err(format!("`{arg}` expects a positive number, got `{value}`")),
+    }
+}
+
+fn parse_whole_number<T: std::str::FromStr>(value: Option<String>, arg: &str) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("`{arg}` expects a number"))?;
+    value
+        .parse()
+        .map_err(|_| format!("`{arg}` expects a whole number, got `{value}`"))
+}
+
+fn run_headless(options: &Options) {
+    let mut game = Assets::load(&mut HeadlessTextures::default())
+        .and_then(|assets| Game::new(&assets, &options.ship, options.seed))
+        .unwrap_or_else(|error| {
+            eprintln!("{error}");
+            std::process::exit(1);
+        });
+
+    let mut playback = options.replay.as_ref().map(Replay::input_source);
+    let mut recording = new_recording(options, options.seed);
+
+    let mut audio = Audio::new(Box::new(NullAudio));
+    let dt = 1.0 / options.tick_rate;
+    let mut event_counts = BTreeMap::new();
+    loop {
+        let input = match &mut playback {
+            Some(playback) if playback.is_finished() => break,
+            Some(playback) => playback.player_input(),
+            None if game.time() >= options.duration => break,
+            None => PlayerInput::default(),
+        };
+        if let Some(recording) = &mut recording {
+            recording.record(input);
+        }
+        game.update(dt, input);
+        for event in game.events() {
+            *event_counts.entry(event.name()).or_insert(0) += 1;
+        }
+        audio.play_events(game.events(), game.camera_position(1.0));
+        audio.update(&game.engines(), game.camera_position(1.0), true);
+        if game.is_over() {
+            break;
+        }
+    }
+    save_recording(recording.as_ref(), options);
+
+    println!("seed: {}", game.seed());
+    println!("time: {:.1}s", game.time());
+    println!("player health: {:.1}%", game.player_health() * 100.0);
+    println!("enemies: {}", game.enemy_count());
+    let bullets = game.bullet_stats();
+    println!(
+        "bullets: {} (peak {}, fired {}, expired {})",
+        bullets.live, bullets.peak, bullets.fired, bullets.expired
+    );
+    println!("particals: {}", game.partical_count());
+    let score = game.score();
+    println!(
+        "score: {} (shot {}, rammed {}, collided {}, best combo {})",
+        score.total(),
+        score.kills(KillCause::Bullet),
+        score.kills(KillCause::Rammed),
+        score.kills(KillCause::Collision),
+        score.best_combo()
+    );
+    let event_counts: Vec<_> = event_counts
+        .iter()
+        .map(|(name, count)| format!("{name} {count}"))
+        .collect();
+    println!("events: {}", event_counts.join(", "));
+    println!("sounds: {}", audio.sounds_played());
+    println!("game over: {}", game.is_over());
+}
+
+#[cfg(feature = "gamepad")]
+fn gamepad_backend() -> Box<dyn GamepadBackend> {
+    match spaceshooter::input::gamepad::GilrsGamepad::new() {
+        Ok(gamepad) => Box::new(gamepad),
+        Err(error) => {
+            eprintln!("gamepads are unavailable: {error}");
+            Box::new(VirtualGamepad::default())
+        }
+    }
+}
+
+#[cfg(not(feature = "gamepad"))]
+fn gamepad_backend() -> Box<dyn GamepadBackend> {
+    Box::new(VirtualGamepad::default())
+}
+
+#[cfg(feature = "audio")]
+fn audio_backend(options: &Options) -> Box<dyn AudioBackend> {
+    if options.mute {
+        return Box::new(NullAudio);
+    }
+    match spaceshooter::audio::RodioAudio::new() {
+        Ok(audio) => Box::new(audio),
+        Err(error) => {
+            eprintln!("sound is unavailable: {error}");
+            Box::new(NullAudio)
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+fn audio_backend(_options: &Options) -> Box<dyn AudioBackend> {
+    Box::new(NullAudio)
+}
+
+fn new_recording(options: &Options, seed: u64) -> Option<Replay> {
+    options
+        .record
+        .as_ref()
+        .map(|_| Replay::new(seed, &options.ship, options.tick_rate))
+}
+
+fn save_recording(recording: Option<&Replay>, options: &Options) {
+    let (Some(recording), Some(path)) = (recording, &options.record) else {
+        return;
+    };
+    match recording.save(path) {
+        Ok(()) => println!("saved replay to {}", path.display()),
+        Err(error) => eprintln!("failed to save replay to {}: {error}", path.display()),
+    }
+}
+
+/// Moves the session between states for a menu action. A restarted game gets a new seed and its
+/// own recording, and ends any playback since a replay only covers the game it was recorded in.
+fn handle_action(
+    action: Action,
+    session: &mut Session,
+    controls: &mut Controls,
+    playback: &mut Option<ScriptedInput>,
+    recording: &mut Option<Replay>,
+    options: &Options,
+) {
+    match (session.state(), action) {
+        (_, Action::ToggleDebug) => session.toggle_debug(),
+        (State::Title, Action::Confirm) => session.start(),
+        (State::Title | State::GameOver, Action::HighScores) => session.show_high_scores(),
+        (State::HighScores, Action::HighScores | Action::Confirm | Action::Pause) => {
+            session.hide_high_scores()
+        }
+        (State::Title | State::Paused, Action::Options) => session.show_options(),
+        (State::Options, Action::Options | Action::Confirm | Action::Pause) => {
+            session.hide_options()
+        }
+        (State::Options, Action::MenuUp | Action::MenuDown) => {
+            session.select_option(action == Action::MenuDown)
+        }
+        (State::Options, Action::TurnLeft | Action::TurnRight) => {
+            session.change_option(action == Action::TurnRight)
+        }
+        (State::Options, Action::Rebind) => {
+            controls.start_rebind(&Action::GAMEPLAY);
+            session.show_rebind(controls.rebinding(), None);
+        }
+        (State::Playing, Action::Pause) => session.pause(),
+        (State::Paused, Action::Pause | Action::Confirm) => session.resume(),
+        (State::Paused | State::GameOver, Action::Restart) | (State::GameOver, Action::Confirm) => {
+            save_recording(recording.as_ref(), options);
+            let seed = rand::random();
+            if let Err(error) = session.restart(seed) {
+                eprintln!("{error}");
+                return;
+            }
+            println!("seed: {seed}");
+            *playback = None;
+            *recording = new_recording(options, seed);
+        }
+        _ => {}
+    }
+}
+
+fn save_bindings(bindings: &Bindings, path: Option<&Path>) {
+    let Some(path) = path else {
+        return;
+    };
+    if let Err(error) = bindings.save(path) {
+        eprintln!("failed to save controls to {}: {error}", path.display());
+    }
+}
+
+/// Shows what a press did to the rebind in progress, and what to press next. The bindings are
+/// saved once the rebind is over.
+fn report_rebind(
+    event: RebindEvent,
+    controls: &Controls,
+    session: &mut Session,
+    path: Option<&Path>,
+) {
+    session.show_rebind(controls.rebinding(), Some(event));
+    if controls.rebinding().is_none() {
+        save_bindings(controls.bindings(), path);
+    }
+}
+
+fn save_settings(settings: &Settings, path: Option<&Path>) {
+    let Some(path) = path else {
+        return;
+    };
+    if let Err(error) = settings.save(path) {
+        eprintln!("failed to save settings to {}: {error}", path.display());
+    }
+}
+
+/// Makes the window and renderer follow `settings`, only changing what differs from `applied`.
+/// Anything the system can't do falls back to something it can, with a warning.
+fn apply_settings(
+    window: &Window,
+    renderer: &mut Renderer,
+    applied: Option<&Settings>,
+    settings: &Settings,
+) {
+    let mode_changed = applied.map(|applied| applied.window_mode) != Some(settings.window_mode);
+    if mode_changed {
+        window.set_fullscreen(fullscreen(window, settings.window_mode));
+    }
+    let size_changed = applied.map(|applied| applied.window_size) != Some(settings.window_size);
+    if settings.window_mode == WindowMode::Windowed && (mode_changed || size_changed) {
+        let (width, height) = settings.window_size;
+        // Some systems resize straight away without sending `Resized`.
+        if let Some(size) = window.request_inner_size(PhysicalSize::new(width, height)) {
+            renderer.resize(size);
+        }
+    }
+    if applied.map(|applied| applied.vsync) != Some(settings.vsync) {
+        let present_mode = renderer.set_present_mode(settings.vsync.present_mode());
+        if present_mode != settings.vsync.present_mode() {
+            eprintln!("{:?} vsync is unavailable, using On", settings.vsync);
+        }
+    }
+    if applied.map(|applied| applied.msaa) != Some(settings.msaa) {
+        let samples = renderer.set_msaa_samples(settings.msaa);
+        if samples != settings.msaa {
+            eprintln!("{}x MSAA is unavailable, using {samples}x", settings.msaa);
+        }
+    }
+}
+
+/// Exclusive fullscreen uses the biggest, fastest video mode of the window's monitor.
+fn fullscreen(window: &Window, mode: WindowMode) -> Option<Fullscreen> {
+    match mode {
+        WindowMode::Windowed => None,
+        WindowMode::Borderless => Some(Fullscreen::Borderless(window.current_monitor())),
+        WindowMode::Fullscreen => {
+            let video_mode = window.current_monitor().and_then(|monitor| {
+                monitor.video_modes().max_by_key(|video_mode| {
+                    let size = video_mode.size();
+                    (
+                        size.width * size.height,
+                        video_mode.refresh_rate_millihertz(),
+                    )
+                })
+            });
+            match video_mode {
+                Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                None => {
+                    eprintln!("exclusive fullscreen is unavailable, using borderless");
+                    Some(Fullscreen::Borderless(window.current_monitor()))
+                }
+            }
+        }
+    }
+}
 
 fn main() {
+    let options = Options::parse().unwrap_or_else(|error| {
+        eprintln!("{error}");
+        std::process::exit(2);
+    });
+
+    if options.headless {
+        run_headless(&options);
+        return;
+    }
+
+    let settings = match &options.settings {
+        Some(path) => Settings::load(path).unwrap_or_else(|error| {
+            eprintln!("{error}, using the default settings");
+            Settings::default()
+        }),
+        None => Settings::default(),
+    };
+
     let event_loop = EventLoop::new().unwrap();
 
+    let (width, height) = settings.window_size;
     let window = Arc::new(
         WindowBuilder::new()
             .with_title("Space Shooter")
+            .with_inner_size(PhysicalSize::new(width, height))
             .with_visible(false)
             .build(&event_loop)
             .unwrap(),
     );
 
-    let mut renderer = pollster::block_on(Renderer::new(window.clone()));
-    let mut game = Game::new(&mut renderer);
+    let mut renderer = pollster::block_on(Renderer::new(
+        window.clone(),
+        settings.gpu.power_preference(),
+    ));
+    if options.cpu_particals {
+        renderer.use_cpu_particals();
+    }
+    apply_settings(&window, &mut renderer, None, &settings);
+    let mut session = Assets::load(&mut renderer)
+        .and_then(|assets| Session::new(assets, &options.ship, options.seed))
+        .unwrap_or_else(|error| {
+            eprintln!("{error}");
+            std::process::exit(1);
+        });
 
-    let mut last_frame = None;
+    println!("seed: {}", session.game().seed());
+    session.set_settings(settings.clone());
+    let mut applied_settings = settings;
+
+    // Replays do not count towards the high scores, so they get a table that is never saved.
+    if options.replay.is_none() {
+        if let Some(path) = &options.high_scores {
+            session.set_high_scores(HighScores::load(path.clone()), &options.name);
+        }
+    }
+
+    let bindings = match &options.bindings {
+        Some(path) => Bindings::load(path).unwrap_or_else(|error| {
+            eprintln!("{error}, using the default controls");
+            Bindings::default()
+        }),
+        None => Bindings::default(),
+    };
+    let mut controls = Controls::new(bindings, gamepad_backend());
+    let mut audio = Audio::new(audio_backend(&options));
+    let mut playback = options.replay.as_ref().map(Replay::input_source);
+    let mut recording = new_recording(&options, options.seed);
+    if playback.is_some() {
+        session.start();
+    }
+    let mut shown_state = None;
+    let mut timestep = FixedTimestep::new(options.tick_rate, options.max_catch_up);
+    let mut last_frame = Instant::now();
 
     window.set_visible(true);
     event_loop.set_control_flow(ControlFlow::Poll);
@@ -41,9 +475,37 @@ fn main() {
                 ..
             } => {
                 window.set_visible(false);
+                save_recording(recording.as_ref(), &options);
                 elwt.exit();
             }
 
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { event, .. },
+                ..
+            } => {
+                // A press that finishes a rebind doesn't also trigger its action.
+                if let Some(rebind) = controls.handle_key_event(&event) {
+                    report_rebind(rebind, &controls, &mut session, options.bindings.as_deref());
+                } else if let Some(action) = controls.triggered_key_action(&event) {
+                    handle_action(
+                        action,
+                        &mut session,
+                        &mut controls,
+                        &mut playback,
+                        &mut recording,
+                        &options,
+                    );
+                }
+            }
+
+            Event::WindowEvent {
+                event: WindowEvent::Focused(false),
+                ..
+            } => {
+                controls.release_all();
+                session.pause();
+            }
+
             Event::WindowEvent {
                 event: WindowEvent::Resized(size),
                 ..
@@ -52,14 +514,74 @@ fn main() {
             }
 
             Event::AboutToWait => {
+                // Waiting between frames instead of polling is what keeps to the cap.
+                if let Some(cap) = session.settings().frame_rate_cap {
+                    let next_frame = last_frame + Duration::from_secs_f64(1.0 / f64::from(cap));
+                    if Instant::now() < next_frame {
+                        elwt.set_control_flow(ControlFlow::WaitUntil(next_frame));
+                        return;
+                    }
+                }
+                elwt.set_control_flow(ControlFlow::Poll);
+
                 let time = Instant::now();
-                let dt = last_frame
-                    .map(|last_frame| time - last_frame)
-                    .unwrap_or(Duration::ZERO)
-                    .as_secs_f32();
-                last_frame = Some(time);
+                let elapsed = (time - last_frame).as_secs_f32();
+                last_frame = time;
+
+                // A press that finishes a rebind doesn't also trigger its action.
+                if let Some(rebind) = controls.poll_gamepad() {
+                    report_rebind(rebind, &controls, &mut session, options.bindings.as_deref());
+                } else {
+                    for action in controls.triggered_gamepad_actions() {
+                        handle_action(
+                            action,
+                            &mut session,
+                            &mut controls,
+                            &mut playback,
+                            &mut recording,
+                            &options,
+                        );
+                    }
+                }
+                let live_input = controls.player_input();
+                if session.settings() != &applied_settings {
+                    apply_settings(
+                        &window,
+                        &mut renderer,
+                        Some(&applied_settings),
+                        session.settings(),
+                    );
+                    applied_settings = session.settings().clone();
+                    save_settings(&applied_settings, options.settings.as_deref());
+                }
+
+                for _ in 0..timestep.advance(elapsed) {
+                    if session.state() != State::Playing {
+                        break;
+                    }
+                    // Once a replay runs out the player takes over from where it ended.
+                    let input = match &mut playback {
+                        Some(playback) if !playback.is_finished() => playback.player_input(),
+                        _ => live_input,
+                    };
+                    if let Some(recording) = &mut recording {
+                        recording.record(input);
+                    }
+                    session.update(timestep.dt(), input);
+                    let game = session.game();
+                    audio.play_events(game.events(), game.camera_position(1.0));
+                }
+                let game = session.game();
+                audio.update(
+                    &game.engines(),
+                    game.camera_position(timestep.alpha()),
+                    session.state() == State::Playing,
+                );
 
-                game.update(dt);
+                if shown_state != Some(session.state()) {
+                    shown_state = Some(session.state());
+                    window.set_title(&format!("Space Shooter - {}", session.state().name()));
+                }
                 window.request_redraw();
             }
 
@@ -78,7 +600,7 @@ fn main() {
                     return;
                 };
 
-                game.render(&mut frame);
+                session.render(&mut frame, timestep.alpha(), controls.bindings());
             }
 
             _ => (),
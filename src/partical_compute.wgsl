@@ -0,0 +1,41 @@
+struct Partical {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    starting_color: vec4<f32>,
+    ending_color: vec4<f32>,
+    size: f32,
+    ending_size: f32,
+    rotation: f32,
+    angular_velocity: f32,
+    time: f32,
+    duration: f32,
+    shape: u32,
+};
+
+struct Step {
+    dt: f32,
+};
+
+@group(0)
+@binding(0)
+var<storage, read_write> particals: array<Partical>;
+
+@group(0)
+@binding(1)
+var<uniform> step: Step;
+
+@compute
+@workgroup_size(64)
+fn simulate(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= arrayLength(&particals) {
+        return;
+    }
+    var partical = particals[id.x];
+    if partical.time >= partical.duration {
+        return;
+    }
+    partical.position += partical.velocity * step.dt;
+    partical.rotation += partical.angular_velocity * step.dt;
+    partical.time += step.dt;
+    particals[id.x] = partical;
+}
@@ -0,0 +1,80 @@
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) partical_index: u32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) @interpolate(flat) shape: u32,
+};
+
+struct Camera {
+    position: vec2<f32>,
+    view_height: f32,
+    aspect: f32,
+};
+
+@group(0)
+@binding(0)
+var<uniform> camera: Camera;
+
+struct Partical {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    starting_color: vec4<f32>,
+    ending_color: vec4<f32>,
+    size: f32,
+    ending_size: f32,
+    rotation: f32,
+    angular_velocity: f32,
+    time: f32,
+    duration: f32,
+    shape: u32,
+};
+
+@group(1)
+@binding(0)
+var<storage, read> particals: array<Partical>;
+
+const SHAPE_CIRCLE: u32 = 1u;
+
+@vertex
+fn vertex(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+
+    output.uv = vec2<f32>(
+        f32((input.vertex_index >> 0u) & 1u),
+        f32((input.vertex_index >> 1u) & 1u),
+    );
+
+    let partical = particals[input.partical_index];
+    let t = partical.time / partical.duration;
+    output.color = mix(partical.starting_color, partical.ending_color, t);
+    output.shape = partical.shape;
+
+    // Dead particals and unused slots collapse to nothing.
+    let size = select(0.0, mix(partical.size, partical.ending_size, t), partical.time < partical.duration);
+    let world_position = rotate_vector((output.uv - 0.5) * size, partical.rotation);
+
+    output.clip_position = vec4<f32>((world_position + partical.position - camera.position) / (camera.view_height * vec2<f32>(camera.aspect, 1.0)), 0.0, 1.0);
+
+    return output;
+}
+
+@fragment
+fn fragment(input: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = length(input.uv - 0.5) * 2.0 - 1.0;
+    let circle_coverage = clamp(0.5 - distance / fwidth(distance), 0.0, 1.0);
+    let coverage = select(1.0, circle_coverage, input.shape == SHAPE_CIRCLE);
+
+    return input.color * vec4<f32>(1.0, 1.0, 1.0, coverage);
+}
+
+fn rotate_vector(vector: vec2<f32>, rotation: f32) -> vec2<f32> {
+    return vec2<f32>(
+        cos(rotation) * vector.x - sin(rotation) * vector.y,
+        sin(rotation) * vector.x + cos(rotation) * vector.y,
+    );
+}
@@ -22,10 +22,15 @@ var<uniform> camera: Camera;
 struct Quad {
     position: vec2<f32>,
     size: vec2<f32>,
+    uv_offset: vec2<f32>,
+    uv_size: vec2<f32>,
     color: vec4<f32>,
     rotation: f32,
+    shape: u32,
 };
 
+const SHAPE_CIRCLE: u32 = 1u;
+
 @group(1)
 @binding(0)
 var<storage, read> quads: array<Quad>;
@@ -59,8 +64,15 @@ fn vertex(input: VertexInput) -> VertexOutput {
 @fragment
 fn fragment(input: VertexOutput) -> @location(0) vec4<f32> {
     let quad = quads[input.quad_index];
-    let texture_color = textureSample(texture, texture_sampler, input.uv);
-    return texture_color * quad.color;
+    let texture_color = textureSample(texture, texture_sampler, quad.uv_offset + input.uv * quad.uv_size);
+
+    // Distance from the edge of the circle that fits in the quad, negative inside, faded over
+    // a pixel so it is smooth at any size.
+    let distance = length(input.uv - 0.5) * 2.0 - 1.0;
+    let circle_coverage = clamp(0.5 - distance / fwidth(distance), 0.0, 1.0);
+    let coverage = select(1.0, circle_coverage, quad.shape == SHAPE_CIRCLE);
+
+    return texture_color * quad.color * vec4<f32>(1.0, 1.0, 1.0, coverage);
 }
 
 fn rotate_vector(vector: vec2<f32>, rotation: f32) -> vec2<f32> {
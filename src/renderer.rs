@@ -1,46 +1,85 @@
 use crate::always_some::AlwaysSome;
 use encase::{ShaderSize, ShaderType, StorageBuffer, UniformBuffer};
+use layout::{Camera, Quad};
+use particals::GpuParticals;
 use slotmap::{SlotMap, SparseSecondaryMap};
 use std::sync::Arc;
-use texture::{Texture, TextureId};
+use text::{Anchor, Font};
+use texture::{Texture, TextureId, TextureLoader};
 use winit::{dpi::PhysicalSize, window::Window};
 
+pub mod particals;
+pub mod text;
 pub mod texture;
 
-#[derive(ShaderType)]
-struct Camera {
-    position: cgmath::Vector2<f32>,
-    view_height: f32,
-    aspect: f32,
-}
+/// The structs the shaders read, in a module of their own because encase's derive adds type
+/// checks that are never called and so trip `dead_code`.
+#[allow(dead_code)]
+mod layout {
+    use encase::ShaderType;
+
+    #[derive(ShaderType)]
+    pub(super) struct Camera {
+        pub(super) position: cgmath::Vector2<f32>,
+        pub(super) view_height: f32,
+        pub(super) aspect: f32,
+    }
 
-#[derive(ShaderType)]
-struct Quad {
-    position: cgmath::Vector2<f32>,
-    size: cgmath::Vector2<f32>,
-    color: cgmath::Vector4<f32>,
-    rotation: f32,
+    #[derive(ShaderType)]
+    pub(super) struct Quad {
+        pub(super) position: cgmath::Vector2<f32>,
+        pub(super) size: cgmath::Vector2<f32>,
+        /// The part of the texture drawn on the quad, in texture coordinates.
+        pub(super) uv_offset: cgmath::Vector2<f32>,
+        pub(super) uv_size: cgmath::Vector2<f32>,
+        pub(super) color: cgmath::Vector4<f32>,
+        pub(super) rotation: f32,
+        /// One of the `SHAPE_` constants, which are the same in the shader.
+        pub(super) shape: u32,
+    }
 }
 
+/// The whole quad.
+const SHAPE_RECTANGLE: u32 = 0;
+/// Only the circle that fits in the quad, with smooth edges at any size.
+const SHAPE_CIRCLE: u32 = 1;
+
 pub struct Renderer {
     window: Arc<Window>,
     surface: wgpu::Surface<'static>,
     surface_config: wgpu::SurfaceConfiguration,
+    adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
     camera_uniform_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    screen_camera_uniform_buffer: wgpu::Buffer,
+    screen_camera_bind_group: wgpu::BindGroup,
     quads_bind_group_layout: wgpu::BindGroupLayout,
-    quad_buffers: SparseSecondaryMap<TextureId, (wgpu::Buffer, wgpu::BindGroup)>,
+    /// Every quad of a frame, one batch after another.
+    quads_storage_buffer: wgpu::Buffer,
+    quads_bind_group: wgpu::BindGroup,
     texture_bind_group_layout: wgpu::BindGroupLayout,
+    quad_shader: wgpu::ShaderModule,
+    quad_render_pipeline_layout: wgpu::PipelineLayout,
     quad_render_pipeline: wgpu::RenderPipeline,
+    background_shader: wgpu::ShaderModule,
+    background_render_pipeline_layout: wgpu::PipelineLayout,
     background_render_pipeline: wgpu::RenderPipeline,
+    /// Samples per pixel, 1 for no MSAA.
+    msaa_samples: u32,
+    /// What the frame is drawn into before being resolved to the surface, when using MSAA.
+    msaa_view: Option<wgpu::TextureView>,
     textures: SlotMap<TextureId, Texture>,
     default_texture: TextureId,
+    font: Font,
+    /// `None` if the GPU can not run compute shaders or particals are simulated on the CPU.
+    gpu_particals: Option<GpuParticals>,
 }
 
 impl Renderer {
-    pub async fn new(window: Arc<Window>) -> Self {
+    /// Starts out with vsync off and no MSAA. Only `power_preference` can't be changed later.
+    pub async fn new(window: Arc<Window>, power_preference: wgpu::PowerPreference) -> Self {
         let size = window.inner_size();
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
@@ -51,7 +90,7 @@ impl Renderer {
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
+                power_preference,
                 compatible_surface: Some(&surface),
                 ..Default::default()
             })
@@ -62,7 +101,9 @@ impl Renderer {
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Renderer Device"),
-                    required_features: wgpu::Features::default(),
+                    // Lets MSAA use every sample count the GPU can do, not just 4.
+                    required_features: adapter.features()
+                        & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
                     required_limits: wgpu::Limits::default(),
                     ..Default::default()
                 },
@@ -118,6 +159,20 @@ impl Renderer {
                 resource: camera_uniform_buffer.as_entire_binding(),
             }],
         });
+        let screen_camera_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screen Camera Uniform Buffer"),
+            size: Camera::SHADER_SIZE.get(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let screen_camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Screen Camera Bind Group"),
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: screen_camera_uniform_buffer.as_entire_binding(),
+            }],
+        });
 
         let quads_storage_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Quads Storage Buffer"),
@@ -183,43 +238,14 @@ impl Renderer {
                 ],
                 push_constant_ranges: &[],
             });
-        let quad_render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Quad Render Pipeline"),
-            layout: Some(&quad_render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &quad_shader,
-                entry_point: "vertex",
-                compilation_options: Default::default(),
-                buffers: &[],
-            },
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &quad_shader,
-                entry_point: "fragment",
-                compilation_options: Default::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::all(),
-                })],
-            }),
-            multiview: None,
-            cache: None,
-        });
+        let quad_render_pipeline = create_render_pipeline(
+            &device,
+            "Quad Render Pipeline",
+            &quad_render_pipeline_layout,
+            &quad_shader,
+            surface_format,
+            1,
+        );
 
         let background_shader =
             device.create_shader_module(wgpu::include_wgsl!("./background_shader.wgsl"));
@@ -230,44 +256,14 @@ impl Renderer {
                 bind_group_layouts: &[&camera_bind_group_layout],
                 push_constant_ranges: &[],
             });
-        let background_render_pipeline =
-            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Background Render Pipeline"),
-                layout: Some(&background_render_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &background_shader,
-                    entry_point: "vertex",
-                    compilation_options: Default::default(),
-                    buffers: &[],
-                },
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleStrip,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &background_shader,
-                    entry_point: "fragment",
-                    compilation_options: Default::default(),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface_format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::all(),
-                    })],
-                }),
-                multiview: None,
-                cache: None,
-            });
+        let background_render_pipeline = create_render_pipeline(
+            &device,
+            "Background Render Pipeline",
+            &background_render_pipeline_layout,
+            &background_shader,
+            surface_format,
+            1,
+        );
 
         let mut textures = SlotMap::with_key();
         let default_texture = textures.insert(Texture::new(
@@ -275,29 +271,60 @@ impl Renderer {
             1,
             1,
             &[255, 255, 255, 255],
+            wgpu::FilterMode::Linear,
             &device,
             &queue,
             &texture_bind_group_layout,
         ));
+        let gpu_particals = adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .then(|| GpuParticals::new(&device, &camera_bind_group_layout, surface_format, 1));
+
+        let font = Font {
+            texture: textures.insert(Font::create_texture(
+                &device,
+                &queue,
+                &texture_bind_group_layout,
+            )),
+        };
 
         Self {
             window,
             surface,
             surface_config,
+            adapter,
             device,
             queue,
             camera_uniform_buffer,
             camera_bind_group,
+            screen_camera_uniform_buffer,
+            screen_camera_bind_group,
             quads_bind_group_layout,
-            quad_buffers: SparseSecondaryMap::new(),
+            quads_storage_buffer,
+            quads_bind_group,
             texture_bind_group_layout,
+            quad_shader,
+            quad_render_pipeline_layout,
             quad_render_pipeline,
+            background_shader,
+            background_render_pipeline_layout,
             background_render_pipeline,
+            msaa_samples: 1,
+            msaa_view: None,
             textures,
             default_texture,
+            font,
+            gpu_particals,
         }
     }
 
+    /// Leaves particals to the game to simulate and draw as quads, even if the GPU could do it.
+    pub fn use_cpu_particals(&mut self) {
+        self.gpu_particals = None;
+    }
+
     pub fn resize(&mut self, size: PhysicalSize<u32>) {
         if size.width == 0 || size.height == 0 {
             return;
@@ -306,20 +333,161 @@ impl Renderer {
         self.surface_config.width = size.width;
         self.surface_config.height = size.height;
         self.surface.configure(&self.device, &self.surface_config);
+        self.create_msaa_view();
     }
 
-    pub fn create_texture(
-        &mut self,
-        label: &str,
-        width: u32,
-        height: u32,
-        pixels: &[u8],
-    ) -> TextureId {
+    /// Picks how frames are shown, falling back to vsync if the surface can't do `present_mode`.
+    /// Returns the mode used.
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) -> wgpu::PresentMode {
+        let supported = self.surface.get_capabilities(&self.adapter).present_modes;
+        let present_mode = match present_mode {
+            // wgpu picks a mode the surface supports for these.
+            wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => present_mode,
+            _ if supported.contains(&present_mode) => present_mode,
+            _ => wgpu::PresentMode::AutoVsync,
+        };
+        if self.surface_config.present_mode != present_mode {
+            self.surface_config.present_mode = present_mode;
+            self.surface.configure(&self.device, &self.surface_config);
+        }
+        present_mode
+    }
+
+    /// Draws with `samples` samples per pixel to smooth edges, or as many as the GPU can do
+    /// below that. Returns the sample count used.
+    pub fn set_msaa_samples(&mut self, samples: u32) -> u32 {
+        let samples = [8, 4, 2, 1]
+            .into_iter()
+            .find(|count| *count <= samples && self.supports_msaa_samples(*count))
+            .unwrap_or(1);
+        if samples == self.msaa_samples {
+            return samples;
+        }
+        self.msaa_samples = samples;
+
+        let format = self.surface_config.format;
+        self.quad_render_pipeline = create_render_pipeline(
+            &self.device,
+            "Quad Render Pipeline",
+            &self.quad_render_pipeline_layout,
+            &self.quad_shader,
+            format,
+            samples,
+        );
+        self.background_render_pipeline = create_render_pipeline(
+            &self.device,
+            "Background Render Pipeline",
+            &self.background_render_pipeline_layout,
+            &self.background_shader,
+            format,
+            samples,
+        );
+        if let Some(gpu_particals) = &mut self.gpu_particals {
+            gpu_particals.set_samples(&self.device, format, samples);
+        }
+        self.create_msaa_view();
+        samples
+    }
+
+    fn supports_msaa_samples(&self, samples: u32) -> bool {
+        if samples == 1 {
+            return true;
+        }
+        // Without adapter specific format features only 4 samples are allowed, which every GPU
+        // supports.
+        let adapter_specific = self
+            .device
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+        let flags = self
+            .adapter
+            .get_texture_format_features(self.surface_config.format)
+            .flags;
+        (adapter_specific || samples == 4)
+            && flags.sample_count_supported(samples)
+            && flags.contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE)
+    }
+
+    fn create_msaa_view(&mut self) {
+        if self.msaa_samples == 1 {
+            self.msaa_view = None;
+            return;
+        }
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Texture"),
+            size: wgpu::Extent3d {
+                width: self.surface_config.width,
+                height: self.surface_config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.msaa_samples,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.surface_config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        self.msaa_view = Some(texture.create_view(&wgpu::TextureViewDescriptor::default()));
+    }
+}
+
+/// A pipeline drawing alpha blended triangle strips into the frame, which has to be made again
+/// whenever the MSAA sample count changes.
+pub(crate) fn create_render_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    samples: u32,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vertex",
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: samples,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fragment",
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::all(),
+            })],
+        }),
+        multiview: None,
+        cache: None,
+    })
+}
+
+impl TextureLoader for Renderer {
+    fn create_texture(&mut self, label: &str, width: u32, height: u32, pixels: &[u8]) -> TextureId {
         self.textures.insert(Texture::new(
             label,
             width,
             height,
             pixels,
+            wgpu::FilterMode::Linear,
             &self.device,
             &self.queue,
             &self.texture_bind_group_layout,
@@ -360,12 +528,23 @@ impl<'renderer> FrameRendering<'renderer> {
         let render_pass = render_encoder
             .begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &output_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(clear_color),
-                        store: wgpu::StoreOp::Store,
+                color_attachments: &[Some(match &renderer.msaa_view {
+                    Some(msaa_view) => wgpu::RenderPassColorAttachment {
+                        view: msaa_view,
+                        resolve_target: Some(&output_view),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(clear_color),
+                            // Only the resolved frame is needed.
+                            store: wgpu::StoreOp::Discard,
+                        },
+                    },
+                    None => wgpu::RenderPassColorAttachment {
+                        view: &output_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(clear_color),
+                            store: wgpu::StoreOp::Store,
+                        },
                     },
                 })],
                 depth_stencil_attachment: None,
@@ -396,10 +575,49 @@ impl Drop for FrameRendering<'_> {
     }
 }
 
+/// Height of the screen layer in its own units, whatever the size of the window.
+pub const SCREEN_HEIGHT: f32 = 1000.0;
+
+/// Depth of a quad in the world. Each layer is drawn over the ones before it, whatever the
+/// textures are, and quads are batched by texture within a layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    Background,
+    Particals,
+    Ships,
+    Bullets,
+    Ui,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 5] = [
+        Layer::Background,
+        Layer::Particals,
+        Layer::Ships,
+        Layer::Bullets,
+        Layer::Ui,
+    ];
+}
+
+/// What a quad's position and size are measured in, in the order they are drawn.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Space {
+    /// Camera units, around the camera.
+    World(Layer),
+    /// Screen units, with `(0, 0)` in the middle of the screen and y going up. Drawn on top of
+    /// the world and not moved by the camera.
+    Screen,
+}
+
 pub struct Rendering2D<'renderer, 'frame> {
     frame: &'frame mut FrameRendering<'renderer>,
+    camera_position: cgmath::Vector2<f32>,
     camera_size: cgmath::Vector2<f32>,
-    quads: SparseSecondaryMap<TextureId, Vec<Quad>>,
+    aspect: f32,
+    /// Indexed by `Layer`.
+    world_quads: [SparseSecondaryMap<TextureId, Vec<Quad>>; Layer::ALL.len()],
+    screen_quads: SparseSecondaryMap<TextureId, Vec<Quad>>,
+    draw_gpu_particals: bool,
 }
 
 impl<'renderer, 'frame> Rendering2D<'renderer, 'frame> {
@@ -408,34 +626,17 @@ impl<'renderer, 'frame> Rendering2D<'renderer, 'frame> {
         camera_position: cgmath::Vector2<f32>,
         camera_height: f32,
     ) -> Self {
-        let renderer = &mut *frame.renderer;
-
-        let window_size = renderer.window.inner_size();
+        let window_size = frame.renderer.window.inner_size();
         let aspect = window_size.width as f32 / window_size.height as f32;
 
-        // Upload camera
-        {
-            let camera = Camera {
-                position: camera_position,
-                view_height: camera_height / 2.0,
-                aspect,
-            };
-
-            let camera_buffer = &mut *renderer
-                .queue
-                .write_buffer_with(&renderer.camera_uniform_buffer, 0, Camera::SHADER_SIZE)
-                .unwrap();
-
-            UniformBuffer::new(camera_buffer).write(&camera).unwrap();
-        }
-        frame
-            .render_pass
-            .set_bind_group(0, &renderer.camera_bind_group, &[]);
-
         Self {
             frame,
+            camera_position,
             camera_size: cgmath::vec2(camera_height * aspect, camera_height),
-            quads: SparseSecondaryMap::new(),
+            aspect,
+            world_quads: Default::default(),
+            screen_quads: SparseSecondaryMap::new(),
+            draw_gpu_particals: false,
         }
     }
 
@@ -443,8 +644,30 @@ impl<'renderer, 'frame> Rendering2D<'renderer, 'frame> {
         self.camera_size
     }
 
+    /// The size of the screen layer in screen units.
+    pub fn screen_size(&self) -> cgmath::Vector2<f32> {
+        cgmath::vec2(SCREEN_HEIGHT * self.aspect, SCREEN_HEIGHT)
+    }
+
+    /// A point on the screen layer, `offset` screen units away from `anchor`.
+    pub fn screen_position(
+        &self,
+        anchor: Anchor,
+        offset: cgmath::Vector2<f32>,
+    ) -> cgmath::Vector2<f32> {
+        anchor.offset(self.screen_size()) + offset
+    }
+
+    /// Where a point in the world is on the screen layer. Points the camera does not see are
+    /// outside of `screen_size`.
+    pub fn world_to_screen(&self, position: cgmath::Vector2<f32>) -> cgmath::Vector2<f32> {
+        (position - self.camera_position) * (SCREEN_HEIGHT / self.camera_size.y)
+    }
+
     pub fn reserve_quads(&mut self, additional: usize) {
-        self.quads.reserve(additional);
+        for quads in &mut self.world_quads {
+            quads.reserve(additional);
+        }
     }
 
     pub fn draw_quad(
@@ -454,17 +677,106 @@ impl<'renderer, 'frame> Rendering2D<'renderer, 'frame> {
         color: cgmath::Vector4<f32>,
         rotation: f32,
         texture: Option<TextureId>,
+        layer: Layer,
     ) {
-        self.quads
-            .entry(texture.unwrap_or(self.frame.renderer.default_texture))
-            .unwrap()
-            .or_default()
-            .push(Quad {
+        let texture = texture.unwrap_or(self.frame.renderer.default_texture);
+        self.draw_quad_region(
+            position,
+            size,
+            color,
+            rotation,
+            texture,
+            layer,
+            cgmath::vec2(0.0, 0.0),
+            cgmath::vec2(1.0, 1.0),
+        );
+    }
+
+    /// Draws part of a texture, like one sprite out of a sheet. `uv_offset` is the bottom left
+    /// corner of the part and `uv_size` its size, both in texture coordinates from 0 to 1.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_quad_region(
+        &mut self,
+        position: cgmath::Vector2<f32>,
+        size: cgmath::Vector2<f32>,
+        color: cgmath::Vector4<f32>,
+        rotation: f32,
+        texture: TextureId,
+        layer: Layer,
+        uv_offset: cgmath::Vector2<f32>,
+        uv_size: cgmath::Vector2<f32>,
+    ) {
+        self.push_quad(
+            Space::World(layer),
+            texture,
+            Quad {
                 position,
                 size,
+                uv_offset,
+                uv_size,
                 color,
                 rotation: rotation.to_radians(),
-            });
+                shape: SHAPE_RECTANGLE,
+            },
+        );
+    }
+
+    /// A filled circle with a `radius` in camera units.
+    pub fn draw_circle(
+        &mut self,
+        position: cgmath::Vector2<f32>,
+        radius: f32,
+        color: cgmath::Vector4<f32>,
+        layer: Layer,
+    ) {
+        let texture = self.frame.renderer.default_texture;
+        self.push_quad(
+            Space::World(layer),
+            texture,
+            Quad {
+                position,
+                size: cgmath::vec2(radius, radius) * 2.0,
+                uv_offset: cgmath::vec2(0.0, 0.0),
+                uv_size: cgmath::vec2(1.0, 1.0),
+                color,
+                rotation: 0.0,
+                shape: SHAPE_CIRCLE,
+            },
+        );
+    }
+
+    /// Like `draw_quad`, but on the screen layer, in screen units. The screen layer is drawn over
+    /// every world `Layer`.
+    pub fn draw_screen_quad(
+        &mut self,
+        position: cgmath::Vector2<f32>,
+        size: cgmath::Vector2<f32>,
+        color: cgmath::Vector4<f32>,
+        rotation: f32,
+        texture: Option<TextureId>,
+    ) {
+        let texture = texture.unwrap_or(self.frame.renderer.default_texture);
+        self.push_quad(
+            Space::Screen,
+            texture,
+            Quad {
+                position,
+                size,
+                uv_offset: cgmath::vec2(0.0, 0.0),
+                uv_size: cgmath::vec2(1.0, 1.0),
+                color,
+                rotation: rotation.to_radians(),
+                shape: SHAPE_RECTANGLE,
+            },
+        );
+    }
+
+    fn push_quad(&mut self, space: Space, texture: TextureId, quad: Quad) {
+        let quads = match space {
+            Space::World(layer) => &mut self.world_quads[layer as usize],
+            Space::Screen => &mut self.screen_quads,
+        };
+        quads.entry(texture).unwrap().or_default().push(quad);
     }
 }
 
@@ -472,79 +784,139 @@ impl Drop for Rendering2D<'_, '_> {
     fn drop(&mut self) {
         let renderer = &mut *self.frame.renderer;
 
+        // Upload cameras
+        for (buffer, camera) in [
+            (
+                &renderer.camera_uniform_buffer,
+                Camera {
+                    position: self.camera_position,
+                    view_height: self.camera_size.y / 2.0,
+                    aspect: self.aspect,
+                },
+            ),
+            (
+                &renderer.screen_camera_uniform_buffer,
+                Camera {
+                    position: cgmath::vec2(0.0, 0.0),
+                    view_height: SCREEN_HEIGHT / 2.0,
+                    aspect: self.aspect,
+                },
+            ),
+        ] {
+            let camera_buffer = &mut *renderer
+                .queue
+                .write_buffer_with(buffer, 0, Camera::SHADER_SIZE)
+                .unwrap();
+
+            UniformBuffer::new(camera_buffer).write(&camera).unwrap();
+        }
+
         // Draw background
         {
+            self.frame
+                .render_pass
+                .set_bind_group(0, &renderer.camera_bind_group, &[]);
             self.frame
                 .render_pass
                 .set_pipeline(&renderer.background_render_pipeline);
             self.frame.render_pass.draw(0..4, 0..1);
         }
 
-        // Draw quads
+        // Gather the batches into one list, the world layers in order and then the screen, with
+        // text last in each, so every batch is a range of the same buffer.
+        let font_texture = renderer.font.texture;
+        let mut quads = Vec::new();
+        let mut batches = Vec::new();
+        let world_layers = Layer::ALL.map(|layer| {
+            (
+                Space::World(layer),
+                std::mem::take(&mut self.world_quads[layer as usize]),
+            )
+        });
+        let screen_layer = (Space::Screen, std::mem::take(&mut self.screen_quads));
+        for (space, layer) in world_layers.into_iter().chain([screen_layer]) {
+            let mut layer: Vec<_> = layer.into_iter().collect();
+            layer.sort_by_key(|(texture, _)| *texture == font_texture);
+            for (texture, layer_quads) in layer {
+                let start = quads.len() as u32;
+                quads.extend(layer_quads);
+                batches.push((space, texture, start..quads.len() as u32));
+            }
+        }
+
+        // Upload quads
+        if !quads.is_empty() {
+            let quads_size = quads.size();
+            if quads_size.get() > renderer.quads_storage_buffer.size() {
+                renderer.quads_storage_buffer =
+                    renderer.device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("Quads Storage Buffer"),
+                        size: quads_size.get().next_power_of_two(),
+                        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    });
+                renderer.quads_bind_group =
+                    renderer
+                        .device
+                        .create_bind_group(&wgpu::BindGroupDescriptor {
+                            label: Some("Quads Bind Group"),
+                            layout: &renderer.quads_bind_group_layout,
+                            entries: &[wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: renderer.quads_storage_buffer.as_entire_binding(),
+                            }],
+                        });
+            }
+
+            let buffer = &mut *renderer
+                .queue
+                .write_buffer_with(&renderer.quads_storage_buffer, 0, quads_size)
+                .unwrap();
+
+            StorageBuffer::new(buffer).write(&quads).unwrap();
+        }
+
+        // Draw quads, with the particals on the GPU drawn over their layer
         {
-            self.frame
-                .render_pass
-                .set_pipeline(&renderer.quad_render_pipeline);
-
-            // Upload quads
-            for (texture, quads) in &self.quads {
-                let quads_size = quads.size();
-
-                let (quads_storage_buffer, quads_bind_group) =
-                    match renderer.quad_buffers.entry(texture).unwrap() {
-                        slotmap::sparse_secondary::Entry::Occupied(occupied_entry)
-                            if quads_size.get() <= occupied_entry.get().0.size() =>
-                        {
-                            occupied_entry.into_mut()
-                        }
-                        _ => {
-                            let quads_storage_buffer =
-                                renderer.device.create_buffer(&wgpu::BufferDescriptor {
-                                    label: Some("Quads Storage Buffer"),
-                                    size: quads_size.get(),
-                                    usage: wgpu::BufferUsages::STORAGE
-                                        | wgpu::BufferUsages::COPY_DST,
-                                    mapped_at_creation: false,
-                                });
-                            let quads_bind_group =
-                                renderer
-                                    .device
-                                    .create_bind_group(&wgpu::BindGroupDescriptor {
-                                        label: Some("Quads Bind Group"),
-                                        layout: &renderer.quads_bind_group_layout,
-                                        entries: &[wgpu::BindGroupEntry {
-                                            binding: 0,
-                                            resource: quads_storage_buffer.as_entire_binding(),
-                                        }],
-                                    });
-                            renderer
-                                .quad_buffers
-                                .insert(texture, (quads_storage_buffer, quads_bind_group));
-                            renderer.quad_buffers.get_mut(texture).unwrap()
-                        }
-                    };
-
-                {
-                    let buffer = &mut *renderer
-                        .queue
-                        .write_buffer_with(quads_storage_buffer, 0, quads_size)
-                        .unwrap();
-
-                    StorageBuffer::new(buffer).write(quads).unwrap();
+            let mut gpu_particals = renderer
+                .gpu_particals
+                .as_ref()
+                .filter(|_| self.draw_gpu_particals);
+            let mut quad_pipeline_set = false;
+            let mut on_screen = false;
+            for (space, texture, range) in batches {
+                if space > Space::World(Layer::Particals) {
+                    if let Some(gpu_particals) = gpu_particals.take() {
+                        gpu_particals.draw(&mut self.frame.render_pass);
+                        quad_pipeline_set = false;
+                    }
+                }
+                if !quad_pipeline_set {
+                    self.frame
+                        .render_pass
+                        .set_pipeline(&renderer.quad_render_pipeline);
+                    self.frame
+                        .render_pass
+                        .set_bind_group(1, &renderer.quads_bind_group, &[]);
+                    quad_pipeline_set = true;
+                }
+                if space == Space::Screen && !on_screen {
+                    self.frame.render_pass.set_bind_group(
+                        0,
+                        &renderer.screen_camera_bind_group,
+                        &[],
+                    );
+                    on_screen = true;
                 }
-
-                self.frame
-                    .render_pass
-                    .set_bind_group(1, quads_bind_group, &[]);
                 self.frame.render_pass.set_bind_group(
                     2,
                     &renderer.textures[texture].bind_group,
                     &[],
                 );
-
-                self.frame
-                    .render_pass
-                    .draw(0..4, 0..quads.len().try_into().unwrap());
+                self.frame.render_pass.draw(0..4, range);
+            }
+            if let Some(gpu_particals) = gpu_particals {
+                gpu_particals.draw(&mut self.frame.render_pass);
             }
         }
     }
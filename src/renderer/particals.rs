@@ -0,0 +1,398 @@
+use cgmath::{Vector2, Vector4};
+use encase::{ShaderSize, ShaderType, StorageBuffer, UniformBuffer};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+use super::{create_render_pipeline, Rendering2D, SHAPE_CIRCLE, SHAPE_RECTANGLE};
+use layout::{GpuPartical, Step};
+
+/// How many particals can be alive at once. Spawning more replaces the oldest ones.
+const CAPACITY: u64 = 1 << 16;
+const WORKGROUP_SIZE: u32 = 64;
+
+/// A partical for the GPU to take over, already `time` seconds into its life.
+#[derive(Clone, Copy, Debug)]
+pub struct ParticalSpawn {
+    pub pos: Vector2<f32>,
+    pub vel: Vector2<f32>,
+    pub size: f32,
+    /// The size at the end of its life, shrinking or growing from `size` on the way.
+    pub ending_size: f32,
+    pub circle: bool,
+    /// In degrees.
+    pub rotation: f32,
+    /// Degrees per second.
+    pub angular_vel: f32,
+    pub starting_color: Vector4<f32>,
+    pub ending_color: Vector4<f32>,
+    pub duration: f32,
+    pub time: f32,
+}
+
+/// Kept apart for the same reason as the renderer's `layout`.
+#[allow(dead_code)]
+mod layout {
+    use super::*;
+
+    #[derive(ShaderType)]
+    pub(super) struct GpuPartical {
+        pub(super) position: Vector2<f32>,
+        pub(super) velocity: Vector2<f32>,
+        pub(super) starting_color: Vector4<f32>,
+        pub(super) ending_color: Vector4<f32>,
+        pub(super) size: f32,
+        pub(super) ending_size: f32,
+        pub(super) rotation: f32,
+        pub(super) angular_velocity: f32,
+        pub(super) time: f32,
+        pub(super) duration: f32,
+        pub(super) shape: u32,
+    }
+
+    #[derive(ShaderType)]
+    pub(super) struct Step {
+        pub(super) dt: f32,
+    }
+}
+
+/// A time by `GpuParticals::clock`, ordered so it can go in a heap.
+#[derive(Clone, Copy)]
+struct Death(f32);
+
+impl PartialEq for Death {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Death {}
+
+impl PartialOrd for Death {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Death {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Particals that live in a ring of slots on the GPU and are moved by a compute shader, so
+/// the CPU only has to send the new ones.
+pub(crate) struct GpuParticals {
+    particals_buffer: wgpu::Buffer,
+    step_buffer: wgpu::Buffer,
+    compute_bind_group: wgpu::BindGroup,
+    compute_pipeline: wgpu::ComputePipeline,
+    render_bind_group: wgpu::BindGroup,
+    render_shader: wgpu::ShaderModule,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    /// The slot the next spawned partical goes in.
+    next_slot: u64,
+    /// One past the highest slot used since the ring was last empty. Slots after it are dead.
+    used_slots: u64,
+    /// Simulated seconds so far, for counting the live particals without reading them back.
+    clock: f32,
+    /// When each partical dies, by `clock`, soonest first. Particals replaced while still
+    /// alive are not taken out, so this can count a few too many when the ring is full.
+    deaths: BinaryHeap<Reverse<Death>>,
+}
+
+impl GpuParticals {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        samples: u32,
+    ) -> Self {
+        let particals_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particals Storage Buffer"),
+            size: GpuPartical::SHADER_SIZE.get() * CAPACITY,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let step_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Partical Step Uniform Buffer"),
+            size: Step::SHADER_SIZE.get(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let compute_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Partical Compute Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: false },
+                            has_dynamic_offset: false,
+                            min_binding_size: Some(GpuPartical::SHADER_SIZE),
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: Some(Step::SHADER_SIZE),
+                        },
+                        count: None,
+                    },
+                ],
+            });
+        let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Partical Compute Bind Group"),
+            layout: &compute_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: particals_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: step_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let render_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Partical Render Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: Some(GpuPartical::SHADER_SIZE),
+                    },
+                    count: None,
+                }],
+            });
+        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Partical Render Bind Group"),
+            layout: &render_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: particals_buffer.as_entire_binding(),
+            }],
+        });
+
+        let compute_shader =
+            device.create_shader_module(wgpu::include_wgsl!("../partical_compute.wgsl"));
+        let compute_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Partical Compute Pipeline Layout"),
+                bind_group_layouts: &[&compute_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Partical Compute Pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &compute_shader,
+            entry_point: "simulate",
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let render_shader =
+            device.create_shader_module(wgpu::include_wgsl!("../partical_shader.wgsl"));
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Partical Render Pipeline Layout"),
+                bind_group_layouts: &[camera_bind_group_layout, &render_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = create_render_pipeline(
+            device,
+            "Partical Render Pipeline",
+            &render_pipeline_layout,
+            &render_shader,
+            format,
+            samples,
+        );
+
+        Self {
+            particals_buffer,
+            step_buffer,
+            compute_bind_group,
+            compute_pipeline,
+            render_bind_group,
+            render_shader,
+            render_pipeline_layout,
+            render_pipeline,
+            next_slot: 0,
+            used_slots: 0,
+            clock: 0.0,
+            deaths: BinaryHeap::new(),
+        }
+    }
+
+    pub(crate) fn set_samples(
+        &mut self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        samples: u32,
+    ) {
+        self.render_pipeline = create_render_pipeline(
+            device,
+            "Partical Render Pipeline",
+            &self.render_pipeline_layout,
+            &self.render_shader,
+            format,
+            samples,
+        );
+    }
+
+    /// Moves every partical `dt` seconds on, then adds `spawns`. `clear` kills every partical
+    /// first.
+    fn simulate(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: f32,
+        clear: bool,
+        spawns: &[ParticalSpawn],
+    ) {
+        {
+            let step_buffer = &mut *queue
+                .write_buffer_with(&self.step_buffer, 0, Step::SHADER_SIZE)
+                .unwrap();
+            UniformBuffer::new(step_buffer).write(&Step { dt }).unwrap();
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Partical Compute Encoder"),
+        });
+        if clear {
+            encoder.clear_buffer(&self.particals_buffer, 0, None);
+            self.deaths.clear();
+        }
+        if self.used_slots > 0 {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Partical Compute Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.compute_pipeline);
+            compute_pass.set_bind_group(0, &self.compute_bind_group, &[]);
+            compute_pass.dispatch_workgroups(
+                (self.used_slots as u32).div_ceil(WORKGROUP_SIZE),
+                1,
+                1,
+            );
+        }
+        // Submitted now so the step runs before the spawns below are written, which only
+        // happens with the next submit.
+        queue.submit(std::iter::once(encoder.finish()));
+        self.clock += dt;
+
+        while self
+            .deaths
+            .peek()
+            .is_some_and(|Reverse(death)| death.0 <= self.clock)
+        {
+            self.deaths.pop();
+        }
+        if self.deaths.is_empty() {
+            // Every slot is dead, so the ring can start again from the first.
+            self.next_slot = 0;
+            self.used_slots = 0;
+        }
+
+        // Only the newest particals fit if there are more than the slots.
+        let spawns = &spawns[spawns.len().saturating_sub(CAPACITY as usize)..];
+        let mut spawns = spawns.iter().peekable();
+        while spawns.peek().is_some() {
+            // Up to the end of the ring at a time, since a write has to be one range.
+            let start = self.next_slot;
+            let batch: Vec<_> = spawns
+                .by_ref()
+                .take((CAPACITY - start) as usize)
+                .map(|spawn| {
+                    self.deaths
+                        .push(Reverse(Death(self.clock + spawn.duration - spawn.time)));
+                    self.used_slots = self.used_slots.max(self.next_slot + 1);
+                    self.next_slot = (self.next_slot + 1) % CAPACITY;
+                    GpuPartical {
+                        position: spawn.pos,
+                        velocity: spawn.vel,
+                        starting_color: spawn.starting_color,
+                        ending_color: spawn.ending_color,
+                        size: spawn.size,
+                        ending_size: spawn.ending_size,
+                        rotation: spawn.rotation.to_radians(),
+                        angular_velocity: spawn.angular_vel.to_radians(),
+                        time: spawn.time,
+                        duration: spawn.duration,
+                        shape: if spawn.circle {
+                            SHAPE_CIRCLE
+                        } else {
+                            SHAPE_RECTANGLE
+                        },
+                    }
+                })
+                .collect();
+
+            let buffer = &mut *queue
+                .write_buffer_with(
+                    &self.particals_buffer,
+                    start * GpuPartical::SHADER_SIZE.get(),
+                    batch.size(),
+                )
+                .unwrap();
+            StorageBuffer::new(buffer).write(&batch).unwrap();
+        }
+    }
+
+    fn live_count(&self) -> usize {
+        self.deaths.len().min(CAPACITY as usize)
+    }
+
+    /// Draws every live partical with the camera that is bound to group 0.
+    pub(crate) fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        if self.used_slots == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(1, &self.render_bind_group, &[]);
+        render_pass.draw(0..4, 0..self.used_slots as u32);
+    }
+}
+
+impl Rendering2D<'_, '_> {
+    /// Whether particals can be handed to `simulate_gpu_particals`. Without it, they have to
+    /// be simulated and drawn as quads.
+    pub fn has_gpu_particals(&self) -> bool {
+        self.frame.renderer.gpu_particals.is_some()
+    }
+
+    /// Moves the particals on the GPU `dt` seconds of game time on and adds `spawns`, which
+    /// are drawn on `Layer::Particals` from then on. `clear` gets rid of the old ones, like
+    /// when a new game starts.
+    pub fn simulate_gpu_particals(&mut self, dt: f32, clear: bool, spawns: &[ParticalSpawn]) {
+        let renderer = &mut *self.frame.renderer;
+        let Some(gpu_particals) = &mut renderer.gpu_particals else {
+            return;
+        };
+        gpu_particals.simulate(&renderer.device, &renderer.queue, dt, clear, spawns);
+        self.draw_gpu_particals = true;
+    }
+
+    /// How many particals are alive on the GPU, if they are simulated there.
+    pub fn gpu_partical_count(&self) -> Option<usize> {
+        self.frame
+            .renderer
+            .gpu_particals
+            .as_ref()
+            .map(GpuParticals::live_count)
+    }
+}
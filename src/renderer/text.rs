@@ -0,0 +1,328 @@
+use cgmath::{vec2, Vector2, Vector4};
+
+use super::{
+    texture::{Texture, TextureId},
+    Layer, Quad, Rendering2D, Space, SHAPE_RECTANGLE,
+};
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+/// Distance from one letter to the next, in font pixels.
+const ADVANCE: f32 = 6.0;
+/// Distance from one line to the next, in font pixels.
+const LINE_HEIGHT: f32 = 10.0;
+
+/// Glyphs are laid out in a grid with a pixel of space around each one, so sampling never
+/// picks up a neighbouring glyph.
+const ATLAS_COLUMNS: usize = 16;
+const ATLAS_ROWS: usize = GLYPHS.len().div_ceil(ATLAS_COLUMNS);
+const CELL_WIDTH: usize = GLYPH_WIDTH + 2;
+const CELL_HEIGHT: usize = GLYPH_HEIGHT + 2;
+const ATLAS_WIDTH: usize = ATLAS_COLUMNS * CELL_WIDTH;
+const ATLAS_HEIGHT: usize = ATLAS_ROWS * CELL_HEIGHT;
+
+/// The printable ASCII characters from ' ' to '~', one byte per column from left to right with
+/// the top row in the lowest bit.
+const GLYPHS: [[u8; GLYPH_WIDTH]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // #
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // )
+    [0x08, 0x2a, 0x1c, 0x2a, 0x08], // *
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // 0
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // @
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // A
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // D
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // G
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // H
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // J
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // M
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // N
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // O
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // Q
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // T
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // U
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // V
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // \
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // f
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // g
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // j
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // l
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // q
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // t
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // u
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // v
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // y
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+/// A point on a rectangle, like the corner of the screen or of a block of text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// The anchor's offset from the center of a rectangle of `size`.
+    pub fn offset(self, size: Vector2<f32>) -> Vector2<f32> {
+        let (x, y) = match self {
+            Anchor::TopLeft => (-0.5, 0.5),
+            Anchor::Top => (0.0, 0.5),
+            Anchor::TopRight => (0.5, 0.5),
+            Anchor::Left => (-0.5, 0.0),
+            Anchor::Center => (0.0, 0.0),
+            Anchor::Right => (0.5, 0.0),
+            Anchor::BottomLeft => (-0.5, -0.5),
+            Anchor::Bottom => (0.0, -0.5),
+            Anchor::BottomRight => (0.5, -0.5),
+        };
+        vec2(size.x * x, size.y * y)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    /// Height of a capital letter, in the units of the layer the text is drawn on.
+    pub size: f32,
+    pub color: Vector4<f32>,
+    /// Which point of the text goes at the position it is drawn at. Lines are lined up on the
+    /// same side, so `Right` right-aligns them and `Center` centers them.
+    pub anchor: Anchor,
+    /// The layer text in the world is drawn on. Text on the screen is always on top.
+    pub layer: Layer,
+}
+
+impl TextStyle {
+    pub fn new(size: f32, color: Vector4<f32>) -> Self {
+        Self {
+            size,
+            color,
+            anchor: Anchor::TopLeft,
+            layer: Layer::Ui,
+        }
+    }
+
+    pub fn anchored(self, anchor: Anchor) -> Self {
+        Self { anchor, ..self }
+    }
+
+    pub fn on_layer(self, layer: Layer) -> Self {
+        Self { layer, ..self }
+    }
+}
+
+/// The built in bitmap font, which covers printable ASCII. Anything else is drawn as `?`.
+pub(crate) struct Font {
+    pub(crate) texture: TextureId,
+}
+
+impl Font {
+    /// RGBA pixels of the glyph atlas, bottom row first like the other textures.
+    fn atlas_pixels() -> Vec<u8> {
+        let mut pixels = vec![0; ATLAS_WIDTH * ATLAS_HEIGHT * 4];
+        for (index, columns) in GLYPHS.iter().enumerate() {
+            let (cell_x, cell_y) = atlas_cell(index);
+            for (x, column) in columns.iter().enumerate() {
+                for y in 0..GLYPH_HEIGHT {
+                    let lit = column & (1 << y) != 0;
+                    let pixel_x = cell_x + 1 + x;
+                    let pixel_y = ATLAS_HEIGHT - 1 - (cell_y + 1 + y);
+                    let offset = (pixel_y * ATLAS_WIDTH + pixel_x) * 4;
+                    pixels[offset..offset + 4].copy_from_slice(&[
+                        255,
+                        255,
+                        255,
+                        if lit { 255 } else { 0 },
+                    ]);
+                }
+            }
+        }
+        pixels
+    }
+
+    pub(crate) fn create_texture(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Texture {
+        Texture::new(
+            "Font",
+            ATLAS_WIDTH as u32,
+            ATLAS_HEIGHT as u32,
+            &Self::atlas_pixels(),
+            wgpu::FilterMode::Nearest,
+            device,
+            queue,
+            bind_group_layout,
+        )
+    }
+
+    /// The bottom left corner and size of a character's glyph in texture coordinates.
+    fn glyph_uv(character: char) -> (Vector2<f32>, Vector2<f32>) {
+        let index = match character {
+            ' '..='~' => character as usize - ' ' as usize,
+            _ => '?' as usize - ' ' as usize,
+        };
+        let (cell_x, cell_y) = atlas_cell(index);
+        let bottom = ATLAS_HEIGHT - (cell_y + 1 + GLYPH_HEIGHT);
+        (
+            vec2(
+                (cell_x + 1) as f32 / ATLAS_WIDTH as f32,
+                bottom as f32 / ATLAS_HEIGHT as f32,
+            ),
+            vec2(
+                GLYPH_WIDTH as f32 / ATLAS_WIDTH as f32,
+                GLYPH_HEIGHT as f32 / ATLAS_HEIGHT as f32,
+            ),
+        )
+    }
+}
+
+/// Top left pixel of a glyph's cell, counting rows from the top of the atlas.
+fn atlas_cell(index: usize) -> (usize, usize) {
+    (
+        index % ATLAS_COLUMNS * CELL_WIDTH,
+        index / ATLAS_COLUMNS * CELL_HEIGHT,
+    )
+}
+
+/// The width and height `text` takes up when drawn at `size`.
+pub fn measure_text(text: &str, size: f32) -> Vector2<f32> {
+    let scale = size / GLYPH_HEIGHT as f32;
+    let lines = text.lines().count().max(1);
+    let longest = text
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    vec2(
+        (longest as f32 * ADVANCE - (ADVANCE - GLYPH_WIDTH as f32)).max(0.0) * scale,
+        (lines as f32 * LINE_HEIGHT - (LINE_HEIGHT - GLYPH_HEIGHT as f32)) * scale,
+    )
+}
+
+impl Rendering2D<'_, '_> {
+    /// Draws `text` in the world with the style's anchor point at `position`. `\n` starts a
+    /// new line.
+    pub fn draw_text(&mut self, text: &str, position: Vector2<f32>, style: &TextStyle) {
+        self.push_text(Space::World(style.layer), text, position, style);
+    }
+
+    /// Like `draw_text`, but on the screen layer, so it stays in the same place on screen
+    /// however the camera moves. `position` is in screen units, see `screen_position`.
+    pub fn draw_screen_text(&mut self, text: &str, position: Vector2<f32>, style: &TextStyle) {
+        self.push_text(Space::Screen, text, position, style);
+    }
+
+    fn push_text(&mut self, space: Space, text: &str, position: Vector2<f32>, style: &TextStyle) {
+        let scale = style.size / GLYPH_HEIGHT as f32;
+        let block_size = measure_text(text, style.size);
+        let top_left =
+            position - style.anchor.offset(block_size) + Anchor::TopLeft.offset(block_size);
+        let texture = self.frame.renderer.font.texture;
+
+        for (line_index, line) in text.lines().enumerate() {
+            let line_width = measure_text(line, style.size).x;
+            // Where the line starts so that it lines up with the anchor's side of the block.
+            let line_x = top_left.x
+                + (block_size.x - line_width) * (style.anchor.offset(vec2(1.0, 0.0)).x + 0.5);
+            let line_top = top_left.y - line_index as f32 * LINE_HEIGHT * scale;
+            for (index, character) in line.chars().enumerate() {
+                if character == ' ' {
+                    continue;
+                }
+                let (uv_offset, uv_size) = Font::glyph_uv(character);
+                self.push_quad(
+                    space,
+                    texture,
+                    Quad {
+                        position: vec2(
+                            line_x + (index as f32 * ADVANCE + GLYPH_WIDTH as f32 / 2.0) * scale,
+                            line_top - GLYPH_HEIGHT as f32 / 2.0 * scale,
+                        ),
+                        size: vec2(GLYPH_WIDTH as f32, GLYPH_HEIGHT as f32) * scale,
+                        uv_offset,
+                        uv_size,
+                        color: style.color,
+                        rotation: 0.0,
+                        shape: SHAPE_RECTANGLE,
+                    },
+                );
+            }
+        }
+    }
+}
@@ -1,19 +1,55 @@
-use slotmap::new_key_type;
+use image::EncodableLayout;
+use slotmap::{new_key_type, SlotMap};
+use std::path::Path;
 
 new_key_type! {
     pub struct TextureId;
 }
 
+pub trait TextureLoader {
+    fn create_texture(&mut self, label: &str, width: u32, height: u32, pixels: &[u8]) -> TextureId;
+
+    fn load_texture(&mut self, label: &str, path: &Path) -> image::ImageResult<TextureId> {
+        let image = image::open(path)?.flipv().into_rgba8();
+        Ok(self.create_texture(label, image.width(), image.height(), image.as_bytes()))
+    }
+}
+
+/// Hands out texture ids without touching the GPU or the image files, for running the game
+/// without a window.
+#[derive(Default)]
+pub struct HeadlessTextures {
+    textures: SlotMap<TextureId, ()>,
+}
+
+impl TextureLoader for HeadlessTextures {
+    fn create_texture(
+        &mut self,
+        _label: &str,
+        _width: u32,
+        _height: u32,
+        _pixels: &[u8],
+    ) -> TextureId {
+        self.textures.insert(())
+    }
+
+    fn load_texture(&mut self, _label: &str, _path: &Path) -> image::ImageResult<TextureId> {
+        Ok(self.textures.insert(()))
+    }
+}
+
 pub struct Texture {
     pub(crate) bind_group: wgpu::BindGroup,
 }
 
 impl Texture {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         label: &str,
         width: u32,
         height: u32,
         pixels: &[u8],
+        filter: wgpu::FilterMode,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         bind_group_layout: &wgpu::BindGroupLayout,
@@ -48,8 +84,8 @@ impl Texture {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
+            mag_filter: filter,
+            min_filter: filter,
             mipmap_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
         });
@@ -0,0 +1,309 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::input::{PlayerInput, ScriptedInput};
+
+const MAGIC: &[u8; 4] = b"SSRP";
+/// Version 1 had no thrust, which reads as full thrust.
+const VERSION: u8 = 2;
+/// The most ticks a replay can hold, over three days at 60 ticks a second. Anything longer is
+/// a broken file, which would otherwise take all the memory there is.
+const MAX_INPUTS: usize = 1 << 24;
+
+const FIRE: u8 = 1 << 0;
+const TURN_LEFT: u8 = 1 << 1;
+const TURN_RIGHT: u8 = 1 << 2;
+const TURN_LEFT_ANALOG: u8 = 1 << 3;
+const TURN_RIGHT_ANALOG: u8 = 1 << 4;
+const NO_THRUST: u8 = 1 << 5;
+const THRUST_ANALOG: u8 = 1 << 6;
+
+/// Everything needed to play a session back exactly: the seed, the ship and the input of every
+/// tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub ship: String,
+    pub tick_rate: f32,
+    pub inputs: Vec<PlayerInput>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, ship: &str, tick_rate: f32) -> Self {
+        Self {
+            seed,
+            ship: ship.to_string(),
+            tick_rate,
+            inputs: vec![],
+        }
+    }
+
+    pub fn record(&mut self, input: PlayerInput) {
+        self.inputs.push(input);
+    }
+
+    pub fn input_source(&self) -> ScriptedInput {
+        ScriptedInput::new(self.inputs.clone())
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Inputs are stored as runs of identical ticks, since held keys rarely change.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.tick_rate.to_le_bytes());
+        write_varint(&mut bytes, self.ship.len() as u64);
+        bytes.extend_from_slice(self.ship.as_bytes());
+
+        let mut index = 0;
+        while index < self.inputs.len() {
+            let input = self.inputs[index];
+            let run = self.inputs[index..]
+                .iter()
+                .take_while(|other| **other == input)
+                .count();
+            write_varint(&mut bytes, run as u64);
+            write_input(&mut bytes, input);
+            index += run;
+        }
+        bytes
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> io::Result<Self> {
+        let reader = &mut bytes;
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a replay file"));
+        }
+        let version = read_u8(reader)?;
+        if !(1..=VERSION).contains(&version) {
+            return Err(invalid_data(format!(
+                "unsupported replay version {version}"
+            )));
+        }
+        let seed = u64::from_le_bytes(read_array(reader)?);
+        let tick_rate = f32::from_le_bytes(read_array(reader)?);
+        if !tick_rate.is_finite() || tick_rate <= 0.0 {
+            return Err(invalid_data(format!("invalid tick rate {tick_rate}")));
+        }
+        let ship_len = read_varint(reader)? as usize;
+        if ship_len > reader.len() {
+            return Err(invalid_data("ship name is cut off"));
+        }
+        let ship = String::from_utf8(reader[..ship_len].to_vec())
+            .map_err(|_| invalid_data("ship name is not valid UTF-8"))?;
+        *reader = &reader[ship_len..];
+
+        let mut inputs = vec![];
+        while !reader.is_empty() {
+            let run = usize::try_from(read_varint(reader)?)
+                .ok()
+                .filter(|run| {
+                    inputs
+                        .len()
+                        .checked_add(*run)
+                        .is_some_and(|total| total <= MAX_INPUTS)
+                })
+                .ok_or_else(|| invalid_data("replay is too long"))?;
+            let input = read_input(reader)?;
+            inputs.extend(std::iter::repeat_n(input, run));
+        }
+
+        Ok(Self {
+            seed,
+            ship,
+            tick_rate,
+            inputs,
+        })
+    }
+}
+
+fn write_input(bytes: &mut Vec<u8>, input: PlayerInput) {
+    let mut flags = 0;
+    if input.fire {
+        flags |= FIRE;
+    }
+    if input.turn_left == 1.0 {
+        flags |= TURN_LEFT;
+    } else if input.turn_left != 0.0 {
+        flags |= TURN_LEFT_ANALOG;
+    }
+    if input.turn_right == 1.0 {
+        flags |= TURN_RIGHT;
+    } else if input.turn_right != 0.0 {
+        flags |= TURN_RIGHT_ANALOG;
+    }
+    if input.thrust == 0.0 {
+        flags |= NO_THRUST;
+    } else if input.thrust != 1.0 {
+        flags |= THRUST_ANALOG;
+    }
+    bytes.push(flags);
+    if flags & TURN_LEFT_ANALOG != 0 {
+        bytes.extend_from_slice(&input.turn_left.to_le_bytes());
+    }
+    if flags & TURN_RIGHT_ANALOG != 0 {
+        bytes.extend_from_slice(&input.turn_right.to_le_bytes());
+    }
+    if flags & THRUST_ANALOG != 0 {
+        bytes.extend_from_slice(&input.thrust.to_le_bytes());
+    }
+}
+
+fn read_input(reader: &mut &[u8]) -> io::Result<PlayerInput> {
+    let flags = read_u8(reader)?;
+    let mut turn = |full, analog| -> io::Result<f32> {
+        Ok(if flags & analog != 0 {
+            f32::from_le_bytes(read_array(reader)?)
+        } else if flags & full != 0 {
+            1.0
+        } else {
+            0.0
+        })
+    };
+    let turn_left = turn(TURN_LEFT, TURN_LEFT_ANALOG)?;
+    let turn_right = turn(TURN_RIGHT, TURN_RIGHT_ANALOG)?;
+    let thrust = if flags & THRUST_ANALOG != 0 {
+        f32::from_le_bytes(read_array(reader)?)
+    } else if flags & NO_THRUST != 0 {
+        0.0
+    } else {
+        1.0
+    };
+    Ok(PlayerInput {
+        turn_left,
+        turn_right,
+        thrust,
+        fire: flags & FIRE != 0,
+    })
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(reader: &mut &[u8]) -> io::Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = read_u8(reader)?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("number is too long"))
+}
+
+fn read_u8(reader: &mut &[u8]) -> io::Result<u8> {
+    Ok(read_array::<1>(reader)?[0])
+}
+
+fn read_array<const N: usize>(reader: &mut &[u8]) -> io::Result<[u8; N]> {
+    let mut array = [0; N];
+    reader.read_exact(&mut array)?;
+    Ok(array)
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay() -> Replay {
+        let mut replay = Replay::new(0x1234_5678_9abc_def0, "Fighter", 60.0);
+        let inputs = [
+            PlayerInput::default(),
+            PlayerInput {
+                turn_left: 1.0,
+                turn_right: 0.0,
+                thrust: 0.0,
+                fire: true,
+            },
+            PlayerInput {
+                turn_left: 0.0,
+                turn_right: 0.25,
+                thrust: 0.5,
+                fire: false,
+            },
+        ];
+        for (input, run) in inputs.into_iter().zip([3, 200, 1]) {
+            for _ in 0..run {
+                replay.record(input);
+            }
+        }
+        replay
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = replay();
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()).unwrap(), replay);
+    }
+
+    #[test]
+    fn version_1_flies_at_full_thrust() {
+        let mut replay = Replay::new(1, "Fighter", 60.0);
+        replay.record(PlayerInput {
+            turn_left: 0.5,
+            fire: true,
+            ..PlayerInput::default()
+        });
+        let mut bytes = replay.to_bytes();
+        bytes[MAGIC.len()] = 1;
+        assert_eq!(Replay::from_bytes(&bytes).unwrap(), replay);
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = replay().to_bytes();
+        for len in 0..bytes.len() - 1 {
+            // Cutting between two runs leaves a valid, shorter replay.
+            if let Ok(replay) = Replay::from_bytes(&bytes[..len]) {
+                assert!(replay.inputs.len() < 204);
+            }
+        }
+        assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Replay::from_bytes(&bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn bad_tick_rate() {
+        for tick_rate in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN, 0.0, -60.0] {
+            let bytes = Replay::new(1, "Fighter", tick_rate).to_bytes();
+            let error = Replay::from_bytes(&bytes).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{tick_rate}");
+        }
+    }
+
+    #[test]
+    fn huge_run() {
+        for run in [u64::MAX, MAX_INPUTS as u64 + 1] {
+            // With no inputs this is only the header.
+            let mut bytes = Replay::new(1, "Fighter", 60.0).to_bytes();
+            write_varint(&mut bytes, run);
+            write_input(&mut bytes, PlayerInput::default());
+            let error = Replay::from_bytes(&bytes).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,432 @@
+use cgmath::{vec2, Vector4};
+use std::time::Instant;
+
+use crate::{
+    game::{Assets, DataError, Game, VIEW_HEIGHT},
+    highscores::{HighScore, HighScores, MAX_HIGH_SCORES},
+    input::{
+        bindings::{Action, Bindings},
+        PlayerInput, RebindEvent,
+    },
+    renderer::{
+        text::{Anchor, TextStyle},
+        FrameRendering, Rendering2D,
+    },
+    settings::{Settings, SettingsOption},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Title,
+    Playing,
+    Paused,
+    GameOver,
+    HighScores,
+    Options,
+}
+
+impl State {
+    pub fn name(self) -> &'static str {
+        match self {
+            State::Title => "Title",
+            State::Playing => "Playing",
+            State::Paused => "Paused",
+            State::GameOver => "Game Over",
+            State::HighScores => "High Scores",
+            State::Options => "Options",
+        }
+    }
+}
+
+/// One game after another: the game only moves while it is being played, and restarting
+/// builds a new one from the same assets.
+pub struct Session {
+    assets: Assets,
+    ship: String,
+    game: Game,
+    state: State,
+    /// Where to go back to from the high score and options screens.
+    previous_state: State,
+    high_scores: HighScores,
+    player_name: String,
+    /// Where the last game landed in the high scores, if it made it in.
+    new_high_score: Option<usize>,
+    settings: Settings,
+    /// Index into `SettingsOption::ALL` of the option being changed.
+    selected_option: usize,
+    /// The action waiting for a key or button on the options screen, while controls change.
+    rebinding: Option<Action>,
+    /// What the last press did to the controls, shown until the options screen closes.
+    rebind_result: Option<RebindEvent>,
+    debug: bool,
+    last_render: Option<Instant>,
+    /// Frames per second, smoothed over the last few frames.
+    fps: f32,
+}
+
+impl Session {
+    pub fn new(assets: Assets, ship: &str, seed: u64) -> Result<Self, DataError> {
+        Ok(Self {
+            game: Game::new(&assets, ship, seed)?,
+            assets,
+            ship: ship.to_string(),
+            state: State::Title,
+            previous_state: State::Title,
+            high_scores: HighScores::default(),
+            player_name: "Player".to_string(),
+            new_high_score: None,
+            settings: Settings::default(),
+            selected_option: 0,
+            rebinding: None,
+            rebind_result: None,
+            debug: false,
+            last_render: None,
+            fps: 0.0,
+        })
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Finished games go into `high_scores` under `player_name`.
+    pub fn set_high_scores(&mut self, high_scores: HighScores, player_name: &str) {
+        self.high_scores = high_scores;
+        self.player_name = player_name.to_string();
+    }
+
+    pub fn high_scores(&self) -> &HighScores {
+        &self.high_scores
+    }
+
+    pub fn new_high_score(&self) -> Option<usize> {
+        self.new_high_score
+    }
+
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+    }
+
+    /// The settings as changed on the options screen, for the window and renderer to follow.
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn start(&mut self) {
+        if self.state == State::Title {
+            self.state = State::Playing;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.state == State::Playing {
+            self.state = State::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.state == State::Paused {
+            self.state = State::Playing;
+        }
+    }
+
+    pub fn show_high_scores(&mut self) {
+        if matches!(self.state, State::Title | State::GameOver) {
+            self.previous_state = self.state;
+            self.state = State::HighScores;
+        }
+    }
+
+    pub fn hide_high_scores(&mut self) {
+        if self.state == State::HighScores {
+            self.state = self.previous_state;
+        }
+    }
+
+    pub fn show_options(&mut self) {
+        if matches!(self.state, State::Title | State::Paused) {
+            self.previous_state = self.state;
+            self.state = State::Options;
+            self.selected_option = 0;
+        }
+    }
+
+    pub fn hide_options(&mut self) {
+        if self.state == State::Options {
+            self.state = self.previous_state;
+            self.rebind_result = None;
+        }
+    }
+
+    /// Shows the rebind in progress on the options screen, with what the last press did to it.
+    pub fn show_rebind(&mut self, rebinding: Option<Action>, result: Option<RebindEvent>) {
+        self.rebinding = rebinding;
+        self.rebind_result = result;
+    }
+
+    /// Moves the selection on the options screen down, or up if `forward` is false.
+    pub fn select_option(&mut self, forward: bool) {
+        let count = SettingsOption::ALL.len();
+        self.selected_option = if forward {
+            (self.selected_option + 1) % count
+        } else {
+            (self.selected_option + count - 1) % count
+        };
+    }
+
+    /// Steps the selected option to its next or previous choice.
+    pub fn change_option(&mut self, forward: bool) {
+        if self.state == State::Options {
+            self.settings
+                .step(SettingsOption::ALL[self.selected_option], forward);
+        }
+    }
+
+    /// Shows or hides hitboxes and numbers for finding out what the game is doing.
+    pub fn toggle_debug(&mut self) {
+        self.debug = !self.debug;
+    }
+
+    /// Throws the current game away and starts playing a new one.
+    pub fn restart(&mut self, seed: u64) -> Result<(), DataError> {
+        self.game = Game::new(&self.assets, &self.ship, seed)?;
+        self.state = State::Playing;
+        self.new_high_score = None;
+        Ok(())
+    }
+
+    pub fn update(&mut self, dt: f32, input: PlayerInput) {
+        if self.state != State::Playing {
+            return;
+        }
+        self.game.update(dt, input);
+        if self.game.is_over() {
+            self.state = State::GameOver;
+            self.record_high_score();
+        }
+    }
+
+    fn record_high_score(&mut self) {
+        self.new_high_score = self.high_scores.add(HighScore {
+            name: self.player_name.clone(),
+            score: self.game.score().total(),
+            time: self.game.time(),
+            seed: self.game.seed(),
+            ship: self.ship.clone(),
+        });
+        if self.new_high_score.is_none() {
+            return;
+        }
+        if let Err(error) = self.high_scores.save() {
+            if let Some(path) = self.high_scores.path() {
+                eprintln!("failed to save high scores to {}: {error}", path.display());
+            }
+        }
+    }
+
+    /// `bindings` are for telling the player which keys to press.
+    pub fn render(&mut self, frame: &mut FrameRendering<'_>, alpha: f32, bindings: &Bindings) {
+        let now = Instant::now();
+        if let Some(last_render) = self.last_render.replace(now) {
+            let fps = 1.0 / (now - last_render).as_secs_f32().max(f32::EPSILON);
+            self.fps += (fps - self.fps) * 0.1;
+        }
+
+        // A game that is not moving is shown where it stopped, not between ticks.
+        let alpha = if self.state == State::Playing {
+            alpha
+        } else {
+            1.0
+        };
+        let mut drawing = Rendering2D::new(frame, self.game.camera_position(alpha), VIEW_HEIGHT);
+        if drawing.has_gpu_particals() {
+            let new_particals = self.game.take_new_particals();
+            drawing.simulate_gpu_particals(
+                new_particals.dt,
+                new_particals.clear,
+                &new_particals.spawns,
+            );
+        }
+        self.game.draw(&mut drawing, alpha);
+        if self.state != State::Title {
+            self.game.draw_hud(&mut drawing);
+        }
+        if self.debug {
+            self.game.draw_debug(&mut drawing, alpha, self.fps);
+        }
+        self.draw_state(&mut drawing, bindings);
+    }
+
+    fn draw_state(&self, drawing: &mut Rendering2D<'_, '_>, bindings: &Bindings) {
+        let (title, prompt) = match self.state {
+            State::Title => (
+                "SPACE SHOOTER",
+                format!(
+                    "Press {} to start\nPress {} for high scores\nPress {} for options",
+                    bindings.key_names(Action::Confirm),
+                    bindings.key_names(Action::HighScores),
+                    bindings.key_names(Action::Options)
+                ),
+            ),
+            State::Playing => return,
+            State::Paused => (
+                "PAUSED",
+                format!(
+                    "Press {} to resume\nPress {} to restart\nPress {} for options",
+                    bindings.key_names(Action::Pause),
+                    bindings.key_names(Action::Restart),
+                    bindings.key_names(Action::Options)
+                ),
+            ),
+            State::GameOver => (
+                "GAME OVER",
+                format!(
+                    "You lasted {:.1}s and scored {}{}\nPress {} to play again\nPress {} for high scores",
+                    self.game.time(),
+                    self.game.score().total(),
+                    match self.new_high_score {
+                        Some(place) => format!("\nNew high score, #{}!", place + 1),
+                        None => String::new(),
+                    },
+                    bindings.key_names(Action::Confirm),
+                    bindings.key_names(Action::HighScores)
+                ),
+            ),
+            State::HighScores => return self.draw_high_scores(drawing, bindings),
+            State::Options => return self.draw_options(drawing, bindings),
+        };
+        let white = Vector4::new(1.0, 1.0, 1.0, 1.0);
+        drawing.draw_screen_text(
+            title,
+            vec2(0.0, 30.0),
+            &TextStyle::new(56.0, white).anchored(Anchor::Bottom),
+        );
+        drawing.draw_screen_text(
+            &prompt,
+            vec2(0.0, 0.0),
+            &TextStyle::new(20.0, white).anchored(Anchor::Top),
+        );
+    }
+
+    /// The table with one row per score, with the last game's row picked out if it made it in.
+    fn draw_high_scores(&self, drawing: &mut Rendering2D<'_, '_>, bindings: &Bindings) {
+        let white = Vector4::new(1.0, 1.0, 1.0, 1.0);
+        let highlight = Vector4::new(1.0, 0.8, 0.2, 1.0);
+        let row_height = 34.0;
+        let top = 280.0;
+        drawing.draw_screen_text(
+            "HIGH SCORES",
+            vec2(0.0, top + 40.0),
+            &TextStyle::new(56.0, white).anchored(Anchor::Bottom),
+        );
+
+        let scores = self.high_scores.scores();
+        if scores.is_empty() {
+            drawing.draw_screen_text(
+                "No scores yet",
+                vec2(0.0, top),
+                &TextStyle::new(20.0, white).anchored(Anchor::Top),
+            );
+        }
+        for (place, score) in scores.iter().enumerate() {
+            let y = top - place as f32 * row_height;
+            let color = if self.new_high_score == Some(place) {
+                highlight
+            } else {
+                white
+            };
+            let style = TextStyle::new(20.0, color);
+            let columns = [
+                (format!("#{}", place + 1), -440.0, Anchor::TopLeft),
+                (score.name.clone(), -380.0, Anchor::TopLeft),
+                (score.score.to_string(), 60.0, Anchor::TopRight),
+                (format!("{:.1}s", score.time), 180.0, Anchor::TopRight),
+                (score.ship.clone(), 220.0, Anchor::TopLeft),
+                (score.seed.to_string(), 480.0, Anchor::TopRight),
+            ];
+            for (text, x, anchor) in columns {
+                drawing.draw_screen_text(&text, vec2(x, y), &style.anchored(anchor));
+            }
+        }
+
+        drawing.draw_screen_text(
+            &format!("Press {} to go back", bindings.key_names(Action::Confirm)),
+            vec2(0.0, top - (MAX_HIGH_SCORES as f32 + 1.0) * row_height),
+            &TextStyle::new(20.0, white).anchored(Anchor::Top),
+        );
+    }
+
+    /// One row per setting with the selected one picked out.
+    fn draw_options(&self, drawing: &mut Rendering2D<'_, '_>, bindings: &Bindings) {
+        let white = Vector4::new(1.0, 1.0, 1.0, 1.0);
+        let highlight = Vector4::new(1.0, 0.8, 0.2, 1.0);
+        let row_height = 34.0;
+        let top = 160.0;
+        drawing.draw_screen_text(
+            "OPTIONS",
+            vec2(0.0, top + 40.0),
+            &TextStyle::new(56.0, white).anchored(Anchor::Bottom),
+        );
+
+        for (index, option) in SettingsOption::ALL.into_iter().enumerate() {
+            let y = top - index as f32 * row_height;
+            let color = if index == self.selected_option {
+                highlight
+            } else {
+                white
+            };
+            let style = TextStyle::new(20.0, color);
+            drawing.draw_screen_text(
+                option.name(),
+                vec2(-20.0, y),
+                &style.anchored(Anchor::TopRight),
+            );
+            drawing.draw_screen_text(
+                &self.settings.value(option),
+                vec2(20.0, y),
+                &style.anchored(Anchor::TopLeft),
+            );
+        }
+
+        let help = match self.rebinding {
+            Some(action) => format!(
+                "Press a key or button for {}, or {} to stop",
+                action.name(),
+                bindings.key_names(Action::Pause)
+            ),
+            None => format!(
+                "Press {} or {} to choose and {} or {} to change\nPress {} to change controls\nPress {} to go back",
+                bindings.key_names(Action::MenuUp),
+                bindings.key_names(Action::MenuDown),
+                bindings.key_names(Action::TurnLeft),
+                bindings.key_names(Action::TurnRight),
+                bindings.key_names(Action::Rebind),
+                bindings.key_names(Action::Confirm)
+            ),
+        };
+        let result = match self.rebind_result {
+            Some(RebindEvent::Bound(action, control)) => {
+                format!("{} is now bound to {}\n", action.name(), control.name())
+            }
+            Some(RebindEvent::Refused(control, owner)) => format!(
+                "{} is used for {}, press something else\n",
+                control.name(),
+                owner.name()
+            ),
+            Some(RebindEvent::Cancelled) => "Stopped changing controls\n".to_string(),
+            None => String::new(),
+        };
+        drawing.draw_screen_text(
+            &(result + &help),
+            vec2(
+                0.0,
+                top - (SettingsOption::ALL.len() as f32 + 1.0) * row_height,
+            ),
+            &TextStyle::new(20.0, white).anchored(Anchor::Top),
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::game::{load_definition, DataError};
+
+/// Window sizes the options screen cycles through.
+const WINDOW_SIZES: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+/// Frame rate caps the options screen cycles through.
+const FRAME_RATE_CAPS: [Option<u32>; 6] =
+    [None, Some(30), Some(60), Some(120), Some(144), Some(240)];
+const MSAA_SAMPLES: [u32; 4] = [1, 2, 4, 8];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowMode {
+    Windowed,
+    /// Covers the whole monitor without changing its video mode.
+    Borderless,
+    Fullscreen,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Vsync {
+    Off,
+    On,
+    /// Waits for the display without holding up the game, where the GPU can do that.
+    Mailbox,
+}
+
+impl Vsync {
+    pub fn present_mode(self) -> wgpu::PresentMode {
+        match self {
+            Vsync::Off => wgpu::PresentMode::AutoNoVsync,
+            Vsync::On => wgpu::PresentMode::AutoVsync,
+            Vsync::Mailbox => wgpu::PresentMode::Mailbox,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GpuPreference {
+    HighPerformance,
+    LowPower,
+}
+
+impl GpuPreference {
+    pub fn power_preference(self) -> wgpu::PowerPreference {
+        match self {
+            GpuPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+            GpuPreference::LowPower => wgpu::PowerPreference::LowPower,
+        }
+    }
+}
+
+/// How the game is shown. Everything but `gpu` can be changed while playing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub window_mode: WindowMode,
+    /// Inner size of the window in pixels, when windowed.
+    pub window_size: (u32, u32),
+    pub vsync: Vsync,
+    /// The most frames drawn per second, or `None` for as many as possible.
+    pub frame_rate_cap: Option<u32>,
+    /// Samples per pixel for smoothing edges: 1, 2, 4 or 8. 1 turns MSAA off.
+    pub msaa: u32,
+    /// Which GPU to ask for when there are several. Only read at startup.
+    pub gpu: GpuPreference,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_mode: WindowMode::Windowed,
+            window_size: (1280, 720),
+            vsync: Vsync::On,
+            frame_rate_cap: None,
+            msaa: 4,
+            gpu: GpuPreference::HighPerformance,
+        }
+    }
+}
+
+impl Settings {
+    /// Where the settings are kept in the user's config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config| config.join("spaceshooter").join("settings.ron"))
+    }
+
+    /// Loads the settings from `path`. Settings the file does not mention keep their defaults,
+    /// and a missing file gives the defaults.
+    pub fn load(path: &Path) -> Result<Self, DataError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let settings: Self = load_definition(path)?;
+        if !MSAA_SAMPLES.contains(&settings.msaa) {
+            return Err(DataError::new(path, "msaa", "must be 1, 2, 4 or 8"));
+        }
+        if settings.window_size.0 == 0 || settings.window_size.1 == 0 {
+            return Err(DataError::new(path, "window_size", "must not be zero"));
+        }
+        if settings.frame_rate_cap == Some(0) {
+            return Err(DataError::new(path, "frame_rate_cap", "must not be zero"));
+        }
+        Ok(settings)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, source)
+    }
+
+    /// How `option` is currently set, for showing on the options screen.
+    pub fn value(&self, option: SettingsOption) -> String {
+        match option {
+            SettingsOption::WindowMode => format!("{:?}", self.window_mode),
+            SettingsOption::WindowSize => {
+                format!("{}x{}", self.window_size.0, self.window_size.1)
+            }
+            SettingsOption::Vsync => format!("{:?}", self.vsync),
+            SettingsOption::FrameRateCap => match self.frame_rate_cap {
+                Some(cap) => cap.to_string(),
+                None => "Off".to_string(),
+            },
+            SettingsOption::Msaa => match self.msaa {
+                1 => "Off".to_string(),
+                samples => format!("{samples}x"),
+            },
+        }
+    }
+
+    /// Moves `option` to its next choice, or its previous one if `forward` is false, wrapping
+    /// around at the ends.
+    pub fn step(&mut self, option: SettingsOption, forward: bool) {
+        match option {
+            SettingsOption::WindowMode => {
+                self.window_mode = step(
+                    &[
+                        WindowMode::Windowed,
+                        WindowMode::Borderless,
+                        WindowMode::Fullscreen,
+                    ],
+                    self.window_mode,
+                    forward,
+                )
+            }
+            SettingsOption::WindowSize => {
+                self.window_size = step(&WINDOW_SIZES, self.window_size, forward)
+            }
+            SettingsOption::Vsync => {
+                self.vsync = step(
+                    &[Vsync::Off, Vsync::On, Vsync::Mailbox],
+                    self.vsync,
+                    forward,
+                )
+            }
+            SettingsOption::FrameRateCap => {
+                self.frame_rate_cap = step(&FRAME_RATE_CAPS, self.frame_rate_cap, forward)
+            }
+            SettingsOption::Msaa => self.msaa = step(&MSAA_SAMPLES, self.msaa, forward),
+        }
+    }
+}
+
+/// The choice after `current` in `choices`. A value that isn't one of the choices, like a
+/// window size from the settings file, goes to the first or last choice.
+fn step<T: Copy + PartialEq>(choices: &[T], current: T, forward: bool) -> T {
+    let index = match choices.iter().position(|choice| *choice == current) {
+        Some(index) if forward => (index + 1) % choices.len(),
+        Some(index) => (index + choices.len() - 1) % choices.len(),
+        None if forward => 0,
+        None => choices.len() - 1,
+    };
+    choices[index]
+}
+
+/// A setting that can be changed on the options screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsOption {
+    WindowMode,
+    WindowSize,
+    Vsync,
+    FrameRateCap,
+    Msaa,
+}
+
+impl SettingsOption {
+    pub const ALL: [SettingsOption; 5] = [
+        SettingsOption::WindowMode,
+        SettingsOption::WindowSize,
+        SettingsOption::Vsync,
+        SettingsOption::FrameRateCap,
+        SettingsOption::Msaa,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingsOption::WindowMode => "Window Mode",
+            SettingsOption::WindowSize => "Window Size",
+            SettingsOption::Vsync => "Vsync",
+            SettingsOption::FrameRateCap => "Frame Rate Cap",
+            SettingsOption::Msaa => "MSAA",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_wraps_around() {
+        let mut settings = Settings {
+            msaa: 8,
+            ..Settings::default()
+        };
+        settings.step(SettingsOption::Msaa, true);
+        assert_eq!(settings.msaa, 1);
+        settings.step(SettingsOption::Msaa, false);
+        assert_eq!(settings.msaa, 8);
+
+        settings.frame_rate_cap = None;
+        settings.step(SettingsOption::FrameRateCap, false);
+        assert_eq!(settings.frame_rate_cap, Some(240));
+        settings.step(SettingsOption::FrameRateCap, true);
+        assert_eq!(settings.frame_rate_cap, None);
+    }
+
+    #[test]
+    fn step_comes_back_after_every_choice() {
+        let mut settings = Settings::default();
+        let original = settings.clone();
+        for option in SettingsOption::ALL {
+            for forward in [true, false] {
+                for _ in 0..7 {
+                    settings.step(option, forward);
+                }
+                for _ in 0..7 {
+                    settings.step(option, !forward);
+                }
+                assert_eq!(settings, original, "{option:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn step_from_an_unlisted_value() {
+        let mut settings = Settings {
+            window_size: (1000, 1000),
+            ..Settings::default()
+        };
+        settings.step(SettingsOption::WindowSize, true);
+        assert_eq!(settings.window_size, WINDOW_SIZES[0]);
+        settings.window_size = (1000, 1000);
+        settings.step(SettingsOption::WindowSize, false);
+        assert_eq!(settings.window_size, WINDOW_SIZES[WINDOW_SIZES.len() - 1]);
+    }
+}
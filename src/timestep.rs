@@ -0,0 +1,70 @@
+/// Turns variable frame times into a whole number of fixed-length simulation ticks.
+pub struct FixedTimestep {
+    dt: f32,
+    max_steps: u32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    /// `max_steps` is how many ticks a single frame may catch up on; time beyond that is dropped
+    /// so one long hitch slows the game down instead of stalling it.
+    pub fn new(tick_rate: f32, max_steps: u32) -> Self {
+        Self {
+            dt: 1.0 / tick_rate,
+            max_steps,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// Adds the time since the last frame and returns how many ticks to run.
+    pub fn advance(&mut self, elapsed: f32) -> u32 {
+        self.accumulator += elapsed;
+        let steps = (self.accumulator / self.dt) as u32;
+        if steps > self.max_steps {
+            self.accumulator = 0.0;
+            return self.max_steps;
+        }
+        self.accumulator -= steps as f32 * self.dt;
+        steps
+    }
+
+    /// How far the current frame is between the last tick and the next, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.dt).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carries_over_partial_ticks() {
+        let mut timestep = FixedTimestep::new(4.0, 5);
+        assert_eq!(timestep.advance(0.625), 2);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(0.125), 1);
+        assert_eq!(timestep.alpha(), 0.0);
+        assert_eq!(timestep.advance(0.125), 0);
+        assert_eq!(timestep.alpha(), 0.5);
+    }
+
+    #[test]
+    fn drops_time_beyond_catch_up() {
+        let mut timestep = FixedTimestep::new(4.0, 5);
+        assert_eq!(timestep.advance(10.0), 5);
+        assert_eq!(timestep.alpha(), 0.0);
+        assert_eq!(timestep.advance(0.25), 1);
+    }
+
+    #[test]
+    fn catches_up_exactly_max_steps() {
+        let mut timestep = FixedTimestep::new(4.0, 5);
+        assert_eq!(timestep.advance(1.375), 5);
+        assert_eq!(timestep.alpha(), 0.5);
+    }
+}